@@ -0,0 +1,81 @@
+//! Forward and backward over the moon batch of 32 through the 2-16-16-1
+//! sample app model: per-sample `MLP::call`, `MLP::forward_batch` over the
+//! scalar graph with shared weight leaves, and the tensor-backed
+//! `MLP::forward_tensor`, one matmul per layer.
+//!
+//! Run with `cargo bench -p nn --bench batch`.
+use nn::mlp::mlp::MLP;
+use nn::mlp::module::Module;
+use nn::tensor::tensor::Tensor;
+use nn::tensor::value::Value;
+use rand::distributions::{Distribution, Uniform};
+use std::time::Instant;
+
+/// Average wall time of `f` in milliseconds.
+fn time<T>(iters: usize, mut f: impl FnMut() -> T) -> f64 {
+    f();
+    let start = Instant::now();
+    for _ in 0..iters {
+        std::hint::black_box(f());
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / iters as f64
+}
+
+/// The sample app's hinge loss over per-sample scores.
+fn loss(scores: &[Value], ys: &[f64]) -> Value {
+    ys.iter()
+        .zip(scores)
+        .map(|(&y, s)| (1.0 + s.clone() * -y).relu())
+        .sum()
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(-1.0..1.0);
+    let xs: Vec<Vec<f64>> = (0..32)
+        .map(|_| vec![die.sample(&mut rng), die.sample(&mut rng)])
+        .collect();
+    let ys: Vec<f64> = (0..32)
+        .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+        .collect();
+    let model = MLP::new(2, &[16, 16, 1]);
+    let iters = 200;
+
+    let per_sample = time(iters, || {
+        xs.iter()
+            .map(|x| model.call(x).unwrap())
+            .collect::<Vec<_>>()
+    });
+    let batch = time(iters, || model.forward_batch(&xs).unwrap());
+    let x = Tensor::newd(xs.concat(), &[32, 2], "x".to_string());
+    let tensor = time(iters, || model.forward_tensor(&x).unwrap());
+    println!(
+        "{:<40} per-sample {:>8.3} ms   batch {:>8.3} ms   tensor {:>8.3} ms",
+        "moon MLP 2-16-16-1, batch 32, fwd", per_sample, batch, tensor
+    );
+
+    let per_sample = time(iters, || {
+        let scores: Vec<Value> = xs.iter().map(|x| model.call(x).unwrap()).collect();
+        model.zero_grad();
+        loss(&scores, &ys).backward();
+    });
+    let batch = time(iters, || {
+        let scores: Vec<Value> = model
+            .forward_batch(&xs)
+            .unwrap()
+            .into_iter()
+            .map(|mut y| y.remove(0))
+            .collect();
+        model.zero_grad();
+        loss(&scores, &ys).backward();
+    });
+    let tensor = time(iters, || {
+        let scores = model.forward_tensor(&x).unwrap().to_values();
+        model.zero_grad();
+        loss(&scores, &ys).backward();
+    });
+    println!(
+        "{:<40} per-sample {:>8.3} ms   batch {:>8.3} ms   tensor {:>8.3} ms",
+        "moon MLP 2-16-16-1, batch 32, fwd+bwd", per_sample, batch, tensor
+    );
+}
@@ -0,0 +1,100 @@
+//! Compares the chunked elementwise kernels in `nn::ops::kernels` with their
+//! one-element-at-a-time fallbacks, and with the scalar `Value` graph.
+//!
+//! Run with `cargo bench -p nn --bench elementwise`.
+use nn::ops::kernels::{self, scalar, Element};
+use nn::tensor::value::Value;
+use rand::distributions::{Distribution, Uniform};
+use std::time::Instant;
+
+const LEN: usize = 1 << 20;
+
+fn random<T: Element>(len: usize) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(-3.0..3.0);
+    (0..len)
+        .map(|_| T::from_f64(die.sample(&mut rng)))
+        .collect()
+}
+
+/// Average wall time of `f` in nanoseconds per element.
+fn time(iters: usize, len: usize, mut f: impl FnMut()) -> f64 {
+    f();
+    let start = Instant::now();
+    for _ in 0..iters {
+        f();
+    }
+    start.elapsed().as_secs_f64() * 1e9 / (iters * len) as f64
+}
+
+fn report(name: &str, scalar: f64, kernel: f64) {
+    println!(
+        "{:<20} scalar {:>7.3} ns/elem   kernel {:>7.3} ns/elem   speedup {:>5.1}x",
+        name,
+        scalar,
+        kernel,
+        scalar / kernel
+    );
+}
+
+type Unary<T> = fn(&[T], &mut [T]);
+type Binary<T> = fn(&[T], &[T], &mut [T]);
+
+fn bench<T: Element>(ty: &str) {
+    let (a, b) = (random::<T>(LEN), random::<T>(LEN));
+    let mut out = vec![T::ZERO; LEN];
+
+    let unary: [(&str, Unary<T>, Unary<T>); 3] = [
+        ("exp", scalar::exp, kernels::exp),
+        ("tanh", scalar::tanh, kernels::tanh),
+        ("relu", scalar::relu, kernels::relu),
+    ];
+    for (name, s, k) in unary {
+        let ts = time(20, LEN, || s(&a, &mut out));
+        let tk = time(20, LEN, || k(&a, &mut out));
+        report(&format!("{} {}", name, ty), ts, tk);
+    }
+
+    let binary: [(&str, Binary<T>, Binary<T>); 5] = [
+        ("add", scalar::add, kernels::add),
+        ("mul", scalar::mul, kernels::mul),
+        ("exp_backward", scalar::exp_backward, kernels::exp_backward),
+        (
+            "tanh_backward",
+            scalar::tanh_backward,
+            kernels::tanh_backward,
+        ),
+        (
+            "relu_backward",
+            scalar::relu_backward,
+            kernels::relu_backward,
+        ),
+    ];
+    for (name, s, k) in binary {
+        let ts = time(20, LEN, || s(&a, &b, &mut out));
+        let tk = time(20, LEN, || k(&a, &b, &mut out));
+        report(&format!("{} {}", name, ty), ts, tk);
+    }
+}
+
+/// Forward and backward of `tanh` through one `Value` node per element.
+fn bench_value() {
+    let len = 1 << 16;
+    let xs: Vec<Value> = random::<f64>(len)
+        .into_iter()
+        .map(|x| Value::newd(x, "x".to_string()))
+        .collect();
+    let t = time(3, len, || {
+        for x in xs.iter() {
+            let y = x.clone().tanh();
+            y.backward();
+        }
+    });
+    println!("{:<20} Value  {:>7.3} ns/elem", "tanh fwd+bwd f64", t);
+}
+
+fn main() {
+    bench::<f64>("f64");
+    bench::<f32>("f32");
+    bench_value();
+}
@@ -0,0 +1,88 @@
+//! Compares dense forward passes through the blocked GEMM kernel
+//! (`Tensor::matmul`) with the scalar `Value` graph built by `MLP::call`.
+//!
+//! Run with `cargo bench -p nn --bench gemm`.
+use nn::mlp::mlp::MLP;
+use nn::tensor::tensor::Tensor;
+use rand::distributions::{Distribution, Uniform};
+use std::time::Instant;
+
+fn random(len: usize) -> Vec<f64> {
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(-1.0..1.0);
+    (0..len).map(|_| die.sample(&mut rng)).collect()
+}
+
+/// Average wall time of `f` in milliseconds.
+fn time<T>(iters: usize, mut f: impl FnMut() -> T) -> f64 {
+    f();
+    let start = Instant::now();
+    for _ in 0..iters {
+        std::hint::black_box(f());
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / iters as f64
+}
+
+fn scalar_forward(model: &MLP, xs: &[Vec<f64>]) -> f64 {
+    xs.iter().map(|x| model.call_unchecked(x).get_data()).sum()
+}
+
+/// Forward pass of a ReLU MLP with a linear last layer, one matmul per layer.
+fn tensor_forward(x: &Tensor, layers: &[(Tensor, Tensor)]) -> Tensor {
+    let mut y = x.clone();
+    for (i, (w, b)) in layers.iter().enumerate() {
+        y = y.matmul(w.clone()) + b.clone();
+        if i != layers.len() - 1 {
+            y = y.relu();
+        }
+    }
+    y
+}
+
+fn bench_mlp(name: &str, nin: usize, nouts: &[usize], batch: usize, iters: usize) {
+    let model = MLP::new(nin, nouts);
+    let xs: Vec<Vec<f64>> = (0..batch).map(|_| random(nin)).collect();
+    let scalar = time(iters, || scalar_forward(&model, &xs));
+
+    let sizes: Vec<usize> = [nin].iter().chain(nouts.iter()).cloned().collect();
+    let layers: Vec<(Tensor, Tensor)> = sizes
+        .windows(2)
+        .map(|s| {
+            (
+                Tensor::newd(random(s[0] * s[1]), &[s[0], s[1]], "w".to_string()),
+                Tensor::newd(vec![0.0; s[1]], &[s[1]], "b".to_string()),
+            )
+        })
+        .collect();
+    let x = Tensor::newd(xs.concat(), &[batch, nin], "x".to_string());
+    let tensor = time(iters, || tensor_forward(&x, &layers));
+
+    println!(
+        "{:<36} scalar {:>10.3} ms   gemm {:>8.3} ms   speedup {:>7.1}x",
+        name,
+        scalar,
+        tensor,
+        scalar / tensor
+    );
+}
+
+fn bench_matmul(n: usize, iters: usize) {
+    let a = Tensor::newd(random(n * n), &[n, n], "a".to_string());
+    let b = Tensor::newd(random(n * n), &[n, n], "b".to_string());
+    let ms = time(iters, || a.clone().matmul(b.clone()));
+    let gflops = 2.0 * (n * n * n) as f64 / (ms * 1e6);
+    println!(
+        "{:<36} gemm {:>10.3} ms   {:>6.2} GFLOP/s",
+        format!("matmul {}x{} @ {}x{}", n, n, n, n),
+        ms,
+        gflops
+    );
+}
+
+fn main() {
+    println!("threads: {}", rayon::current_num_threads());
+    bench_mlp("moon MLP 2-16-16-1, batch 32", 2, &[16, 16, 1], 32, 50);
+    bench_mlp("dense 512x512, batch 1", 512, &[512], 1, 3);
+    bench_mlp("dense 512x512, batch 32", 512, &[512], 32, 1);
+    bench_matmul(512, 10);
+}
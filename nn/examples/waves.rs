@@ -0,0 +1,138 @@
+//! Classifies synthetic sine and square waves with a small 1-D convnet:
+//! two convolutions, global average pooling and a linear head, trained with
+//! full-batch gradient descent.
+//!
+//!     cargo run --release -p nn --example waves
+
+use nn::conv::conv1d::{Conv1d, GlobalAveragePool1d};
+use nn::mlp::init::KaimingUniform;
+use nn::mlp::module::Module;
+use nn::tensor::value::Value;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+
+const LENGTH: usize = 32;
+
+/// `n` waves of random frequency and phase with a little noise, labelled 0
+/// for sine and 1 for square.
+fn waves(n: usize, rng: &mut StdRng) -> (Vec<f64>, Vec<usize>) {
+    let (mut xs, mut ys) = (vec![], vec![]);
+    for i in 0..n {
+        let label = i % 2;
+        let freq = rng.gen_range(1.5..4.0) / LENGTH as f64;
+        let phase = rng.gen_range(0.0..2.0 * PI);
+        for t in 0..LENGTH {
+            let s = (2.0 * PI * freq * t as f64 + phase).sin();
+            let s = if label == 1 { s.signum() } else { s };
+            xs.push(s + rng.gen_range(-0.1..0.1));
+        }
+        ys.push(label);
+    }
+    (xs, ys)
+}
+
+struct Model {
+    conv1: Conv1d,
+    conv2: Conv1d,
+    pool: GlobalAveragePool1d,
+    head: Vec<Value>,
+}
+
+impl Model {
+    fn new(rng: &mut StdRng) -> Model {
+        Model {
+            conv1: Conv1d::new(1, 4, 3, &KaimingUniform, rng).padding(1),
+            conv2: Conv1d::new(4, 4, 3, &KaimingUniform, rng)
+                .dilation(2)
+                .causal(),
+            pool: GlobalAveragePool1d::new(),
+            head: (0..10)
+                .map(|i| Value::newd(0.0, format!("h{}", i)))
+                .collect(),
+        }
+    }
+
+    /// Two logits per sample.
+    fn call(&self, x: &[Value]) -> Vec<Vec<Value>> {
+        let h: Vec<Value> = self
+            .conv1
+            .call(x, LENGTH)
+            .into_iter()
+            .map(|v| v.relu())
+            .collect();
+        let h: Vec<Value> = self
+            .conv2
+            .call(&h, LENGTH)
+            .into_iter()
+            .map(|v| v.relu())
+            .collect();
+        let h = self.pool.call(&h, LENGTH);
+        h.chunks(4)
+            .map(|f| {
+                (0..2)
+                    .map(|k| {
+                        Value::dot(&self.head[k * 5..k * 5 + 4], f) + self.head[k * 5 + 4].clone()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Module for Model {
+    fn parameters(&self) -> Vec<&Value> {
+        let mut p = self.conv1.parameters();
+        p.extend(self.conv2.parameters());
+        p.extend(self.head.iter());
+        p
+    }
+}
+
+fn accuracy(model: &Model, xs: &[f64], ys: &[usize]) -> f64 {
+    let correct = model
+        .call(&Value::vec(xs))
+        .iter()
+        .zip(ys)
+        .filter(|(logits, &y)| (logits[1].get_data() > logits[0].get_data()) as usize == y)
+        .count();
+    correct as f64 / ys.len() as f64
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let (train_x, train_y) = waves(40, &mut rng);
+    let (test_x, test_y) = waves(40, &mut rng);
+    let model = Model::new(&mut rng);
+    for (i, w) in model.head.iter().enumerate() {
+        w.set_data(if i % 5 == 4 {
+            0.0
+        } else {
+            rng.gen_range(-0.5..0.5)
+        });
+    }
+
+    for epoch in 0..200 {
+        let x = Value::vec(&train_x);
+        let losses: Vec<Value> = model
+            .call(&x)
+            .iter()
+            .zip(&train_y)
+            .map(|(logits, &y)| -Value::log_softmax(logits)[y].clone())
+            .collect();
+        let loss = Value::sum_all(&losses) * (1.0 / losses.len() as f64);
+        model.zero_grad();
+        loss.backward();
+        for p in model.parameters() {
+            p.set_data(p.get_data() - 0.5 * p.get_grad());
+        }
+        if epoch % 20 == 0 {
+            println!("epoch {:2} loss {:.4}", epoch, loss.get_data());
+        }
+    }
+    println!(
+        "train accuracy {:.2}, test accuracy {:.2}",
+        accuracy(&model, &train_x, &train_y),
+        accuracy(&model, &test_x, &test_y)
+    );
+}
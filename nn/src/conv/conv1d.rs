@@ -0,0 +1,313 @@
+use crate::conv::conv2d::init;
+use crate::mlp::init::Initializer;
+use crate::mlp::module::Module;
+use crate::tensor::value::Value;
+use rand::RngCore;
+use std::rc::Rc;
+
+/// 1-D convolution over sequences.
+///
+/// Inputs are flat, row-major `[batch, in_channels, length]` slices of
+/// `Value` and outputs `[batch, out_channels, out_length]`, the batch size
+/// following from the input length. As in [`Conv2d`], each output is one
+/// `dot` node plus the bias, and padded positions are left out of the dot
+/// product.
+///
+/// [`Conv2d`]: crate::conv::conv2d::Conv2d
+#[derive(Debug)]
+pub struct Conv1d {
+    in_channels: usize,
+    out_channels: usize,
+    kernel_size: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+    causal: bool,
+    // [out_channels, in_channels, kernel_size]
+    w: Rc<Vec<Value>>,
+    b: Rc<Vec<Value>>,
+}
+
+impl Conv1d {
+    /// Stride 1, no padding and no dilation. Weights and biases come from
+    /// `init` with `fan_in = in_channels * kernel_size`, drawn from `rng`.
+    pub fn new(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: usize,
+        init: &dyn Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Conv1d {
+        assert!(kernel_size > 0, "Conv1d: kernel_size must be positive");
+        let (w, b) = self::init(out_channels, in_channels * kernel_size, init, rng);
+        Conv1d {
+            in_channels,
+            out_channels,
+            kernel_size,
+            stride: 1,
+            padding: 0,
+            dilation: 1,
+            causal: false,
+            w,
+            b,
+        }
+    }
+
+    pub fn stride(mut self, stride: usize) -> Conv1d {
+        assert!(stride > 0, "Conv1d: stride must be positive");
+        self.stride = stride;
+        self
+    }
+
+    /// Zero padding added on both ends. Ignored when causal.
+    pub fn padding(mut self, padding: usize) -> Conv1d {
+        self.padding = padding;
+        self
+    }
+
+    /// Spacing between kernel taps.
+    pub fn dilation(mut self, dilation: usize) -> Conv1d {
+        assert!(dilation > 0, "Conv1d: dilation must be positive");
+        self.dilation = dilation;
+        self
+    }
+
+    /// Pads only the start of the sequence, by the kernel span minus one, so
+    /// output `t` sees inputs up to `t * stride` and no later ones. With
+    /// stride 1 the output is as long as the input.
+    pub fn causal(mut self) -> Conv1d {
+        self.causal = true;
+        self
+    }
+
+    pub fn in_channels(&self) -> usize {
+        self.in_channels
+    }
+
+    pub fn out_channels(&self) -> usize {
+        self.out_channels
+    }
+
+    /// Padding before and after the sequence.
+    fn pads(&self) -> (usize, usize) {
+        if self.causal {
+            (self.dilation * (self.kernel_size - 1), 0)
+        } else {
+            (self.padding, self.padding)
+        }
+    }
+
+    /// Length of the output for an input of `length`.
+    pub fn output_size(&self, length: usize) -> usize {
+        let span = self.dilation * (self.kernel_size - 1) + 1;
+        let (before, after) = self.pads();
+        let padded = before + length + after;
+        assert!(
+            padded >= span,
+            "Conv1d: padded input length {} is smaller than the kernel span {}",
+            padded,
+            span
+        );
+        (padded - span) / self.stride + 1
+    }
+
+    pub fn call(&self, x: &[Value], length: usize) -> Vec<Value> {
+        let sample = self.in_channels * length;
+        assert!(
+            sample > 0 && x.len().is_multiple_of(sample),
+            "Conv1d: {} inputs do not split into samples of {}x{}",
+            x.len(),
+            self.in_channels,
+            length
+        );
+        let batch = x.len() / sample;
+        let out_len = self.output_size(length);
+        let (before, _) = self.pads();
+        let k = self.kernel_size;
+
+        let mut out = Vec::with_capacity(batch * self.out_channels * out_len);
+        for n in 0..batch {
+            let x = &x[n * sample..(n + 1) * sample];
+            for o in 0..self.out_channels {
+                for t in 0..out_len {
+                    let (mut ws, mut xs) = (vec![], vec![]);
+                    for c in 0..self.in_channels {
+                        for kt in 0..k {
+                            let Some(i) = (t * self.stride + kt * self.dilation)
+                                .checked_sub(before)
+                                .filter(|&i| i < length)
+                            else {
+                                continue;
+                            };
+                            ws.push(self.w[(o * self.in_channels + c) * k + kt].clone());
+                            xs.push(x[c * length + i].clone());
+                        }
+                    }
+                    out.push(Value::dot(&ws, &xs) + self.b[o].clone());
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Module for Conv1d {
+    fn parameters(&self) -> Vec<&Value> {
+        self.w.iter().chain(self.b.iter()).collect()
+    }
+
+    fn named_parameters(&self) -> Vec<(String, &Value)> {
+        let w = self
+            .w
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (format!("w{}", i), w));
+        let b = self
+            .b
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (format!("b{}", i), b));
+        w.chain(b).collect()
+    }
+}
+
+/// Mean over the length of every channel: `[batch, channels, length]` to
+/// `[batch, channels]`.
+#[derive(Debug, Default)]
+pub struct GlobalAveragePool1d;
+
+impl GlobalAveragePool1d {
+    pub fn new() -> GlobalAveragePool1d {
+        GlobalAveragePool1d
+    }
+
+    pub fn call(&self, x: &[Value], length: usize) -> Vec<Value> {
+        assert!(
+            length > 0 && x.len().is_multiple_of(length),
+            "GlobalAveragePool1d: {} inputs do not split into rows of {}",
+            x.len(),
+            length
+        );
+        let scale = 1.0 / length as f64;
+        x.chunks(length)
+            .map(|row| Value::sum_all(row) * scale)
+            .collect()
+    }
+}
+
+impl Module for GlobalAveragePool1d {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mlp::init::KaimingUniform;
+    use crate::ops::gradcheck::check_grad;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn conv1d(in_channels: usize, out_channels: usize, kernel_size: usize) -> Conv1d {
+        let mut rng = StdRng::seed_from_u64(0);
+        Conv1d::new(
+            in_channels,
+            out_channels,
+            kernel_size,
+            &KaimingUniform,
+            &mut rng,
+        )
+    }
+
+    fn input(len: usize) -> Vec<Value> {
+        Value::vec(
+            &(0..len)
+                .map(|i| ((i * 7) % 11) as f64 / 5.0 - 1.0)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn data(xs: &[Value]) -> Vec<f64> {
+        xs.iter().map(|x| x.get_data()).collect()
+    }
+
+    #[test]
+    fn test_output_size() {
+        assert_eq!(conv1d(1, 1, 3).output_size(10), 8);
+        assert_eq!(conv1d(1, 1, 3).padding(1).output_size(10), 10);
+        assert_eq!(conv1d(1, 1, 3).stride(2).output_size(10), 4);
+        assert_eq!(conv1d(1, 1, 3).dilation(3).output_size(10), 4);
+        assert_eq!(conv1d(1, 1, 3).dilation(3).causal().output_size(10), 10);
+        assert_eq!(conv1d(1, 1, 4).stride(3).causal().output_size(10), 4);
+    }
+
+    #[test]
+    fn test_conv1d() {
+        // Batch of two, differences x[t + 1] - x[t].
+        let conv = conv1d(1, 1, 2);
+        conv.w[0].set_data(-1.0);
+        conv.w[1].set_data(1.0);
+        let x = Value::vec(&[1.0, 2.0, 4.0, 8.0, 0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(data(&conv.call(&x, 4)), vec![1.0, 2.0, 4.0, 1.0, -1.0, 1.0]);
+        assert_eq!(conv.parameters().len(), 3);
+    }
+
+    #[test]
+    fn test_causal() {
+        // Output t is x[t] + x[t - 2] + x[t - 4], with missing taps left out.
+        let conv = conv1d(1, 1, 3).dilation(2).causal();
+        conv.w.iter().for_each(|w| w.set_data(1.0));
+        let x = Value::vec(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let out = conv.call(&x, 6);
+        assert_eq!(data(&out), vec![1.0, 2.0, 4.0, 6.0, 9.0, 12.0]);
+
+        // Nothing flows back from an output to later inputs.
+        out[3].backward();
+        let grads: Vec<f64> = x.iter().map(|x| x.get_grad()).collect();
+        assert_eq!(grads, vec![0.0, 1.0, 0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_conv1d_grad() {
+        for conv in [
+            conv1d(2, 3, 3),
+            conv1d(2, 2, 3).stride(2).padding(1),
+            conv1d(2, 2, 2).dilation(2).causal(),
+            conv1d(2, 1, 3).stride(2).causal(),
+        ] {
+            // Batch of two sequences of length 7.
+            let x = input(2 * 2 * 7);
+            conv.b.iter().for_each(|b| b.set_data(0.1));
+            let leaves: Vec<&Value> = x.iter().chain(conv.parameters()).collect();
+            check_grad(|| conv.call(&x, 7), &leaves);
+        }
+    }
+
+    #[test]
+    fn test_global_average_pool() {
+        let x = Value::vec(&[1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 2.0, 2.0]);
+        let pool = GlobalAveragePool1d::new();
+        assert_eq!(data(&pool.call(&x, 4)), vec![2.5, 1.0]);
+        assert!(pool.parameters().is_empty());
+
+        let x = input(12);
+        let leaves: Vec<&Value> = x.iter().collect();
+        check_grad(|| pool.call(&x, 3), &leaves);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_conv1d_bad_shape() {
+        conv1d(2, 1, 2).call(&Value::vec(&[1.0; 9]), 4);
+    }
+
+    #[test]
+    fn test_named_parameters() {
+        let conv = conv1d(2, 3, 2);
+        let names: Vec<String> = conv
+            .named_parameters()
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(names.len(), conv.num_parameters());
+        assert_eq!(names[11], "w11");
+        assert_eq!(names[12..], ["b0", "b1", "b2"]);
+    }
+}
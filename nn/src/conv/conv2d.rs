@@ -0,0 +1,383 @@
+use crate::mlp::init::Initializer;
+use crate::mlp::module::Module;
+use crate::tensor::value::Value;
+use rand::RngCore;
+use std::rc::Rc;
+
+/// The `out_channels x fan_in` weights and `out_channels` biases of a
+/// convolution, from `init` as for a dense layer with one neuron per output
+/// channel.
+pub(crate) fn init(
+    out_channels: usize,
+    fan_in: usize,
+    init: &dyn Initializer,
+    rng: &mut dyn RngCore,
+) -> (Rc<Vec<Value>>, Rc<Vec<Value>>) {
+    let w = init.weights(fan_in, out_channels, rng);
+    let b = init.biases(fan_in, out_channels, rng);
+    let w = w
+        .into_iter()
+        .enumerate()
+        .map(|(i, w)| Value::newd(w, format!("w{}", i)))
+        .collect();
+    let b = b
+        .into_iter()
+        .enumerate()
+        .map(|(i, b)| Value::newd(b, format!("b{}", i)))
+        .collect();
+    (Rc::new(w), Rc::new(b))
+}
+
+/// 2-D convolution with a square kernel.
+///
+/// Inputs are feature maps stored as flat, row-major
+/// `[batch, in_channels, height, width]` slices of `Value` and outputs
+/// `[batch, out_channels, out_height, out_width]`, the batch size following
+/// from the input length. Each output is one `dot` node over the weights and
+/// the inputs under the kernel, plus the bias.
+/// Padding is implicit: positions outside the input are left out of the dot
+/// product, which is the same as zero padding.
+#[derive(Debug)]
+pub struct Conv2d {
+    in_channels: usize,
+    out_channels: usize,
+    kernel_size: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+    // [out_channels, in_channels, kernel_size, kernel_size]
+    w: Rc<Vec<Value>>,
+    b: Rc<Vec<Value>>,
+}
+
+impl Conv2d {
+    /// Stride 1, no padding and no dilation. Weights and biases come from
+    /// `init` with `fan_in = in_channels * kernel_size^2` and one row per
+    /// output channel, drawn from `rng`.
+    pub fn new(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: usize,
+        init: &dyn Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Conv2d {
+        assert!(kernel_size > 0, "Conv2d: kernel_size must be positive");
+        let fan_in = in_channels * kernel_size * kernel_size;
+        let (w, b) = self::init(out_channels, fan_in, init, rng);
+        Conv2d {
+            in_channels,
+            out_channels,
+            kernel_size,
+            stride: 1,
+            padding: 0,
+            dilation: 1,
+            w,
+            b,
+        }
+    }
+
+    pub fn stride(mut self, stride: usize) -> Conv2d {
+        assert!(stride > 0, "Conv2d: stride must be positive");
+        self.stride = stride;
+        self
+    }
+
+    /// Zero padding added on every side.
+    pub fn padding(mut self, padding: usize) -> Conv2d {
+        self.padding = padding;
+        self
+    }
+
+    /// Spacing between kernel taps.
+    pub fn dilation(mut self, dilation: usize) -> Conv2d {
+        assert!(dilation > 0, "Conv2d: dilation must be positive");
+        self.dilation = dilation;
+        self
+    }
+
+    pub fn in_channels(&self) -> usize {
+        self.in_channels
+    }
+
+    pub fn out_channels(&self) -> usize {
+        self.out_channels
+    }
+
+    /// Height and width of the output for an input of `height x width`.
+    pub fn output_size(&self, height: usize, width: usize) -> (usize, usize) {
+        let span = self.dilation * (self.kernel_size - 1) + 1;
+        let (h, w) = (height + 2 * self.padding, width + 2 * self.padding);
+        assert!(
+            h >= span && w >= span,
+            "Conv2d: padded input {}x{} is smaller than the kernel span {}",
+            h,
+            w,
+            span
+        );
+        ((h - span) / self.stride + 1, (w - span) / self.stride + 1)
+    }
+
+    /// Convolves `x`, a batch of `[in_channels, height, width]` feature maps,
+    /// into `[out_channels, out_height, out_width]` ones.
+    pub fn call(&self, x: &[Value], height: usize, width: usize) -> Vec<Value> {
+        let sample = self.in_channels * height * width;
+        assert!(
+            sample > 0 && x.len().is_multiple_of(sample),
+            "Conv2d: {} inputs do not split into samples of {}x{}x{}",
+            x.len(),
+            self.in_channels,
+            height,
+            width
+        );
+        let batch = x.len() / sample;
+        let (oh, ow) = self.output_size(height, width);
+        let k = self.kernel_size;
+        // Input coordinate of kernel tap `t` for output position `o`.
+        let at = |o: usize, t: usize, size: usize| -> Option<usize> {
+            (o * self.stride + t * self.dilation)
+                .checked_sub(self.padding)
+                .filter(|&i| i < size)
+        };
+
+        let mut out = Vec::with_capacity(batch * self.out_channels * oh * ow);
+        for n in 0..batch {
+            let x = &x[n * sample..(n + 1) * sample];
+            for o in 0..self.out_channels {
+                for i in 0..oh {
+                    for j in 0..ow {
+                        let (mut ws, mut xs) = (vec![], vec![]);
+                        for c in 0..self.in_channels {
+                            for ki in 0..k {
+                                let Some(r) = at(i, ki, height) else { continue };
+                                for kj in 0..k {
+                                    let Some(col) = at(j, kj, width) else {
+                                        continue;
+                                    };
+                                    let w = ((o * self.in_channels + c) * k + ki) * k + kj;
+                                    ws.push(self.w[w].clone());
+                                    xs.push(x[(c * height + r) * width + col].clone());
+                                }
+                            }
+                        }
+                        out.push(Value::dot(&ws, &xs) + self.b[o].clone());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Module for Conv2d {
+    fn parameters(&self) -> Vec<&Value> {
+        self.w.iter().chain(self.b.iter()).collect()
+    }
+
+    fn named_parameters(&self) -> Vec<(String, &Value)> {
+        let w = self
+            .w
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (format!("w{}", i), w));
+        let b = self
+            .b
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (format!("b{}", i), b));
+        w.chain(b).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::pool2d::MaxPool2d;
+    use crate::mlp::init::{KaimingUniform, Orthogonal};
+    use crate::ops::gradcheck::check_grad;
+    use rand::distributions::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn conv2d(in_channels: usize, out_channels: usize, kernel_size: usize) -> Conv2d {
+        let mut rng = StdRng::seed_from_u64(0);
+        Conv2d::new(
+            in_channels,
+            out_channels,
+            kernel_size,
+            &KaimingUniform,
+            &mut rng,
+        )
+    }
+
+    fn input(len: usize) -> Vec<Value> {
+        Value::vec(
+            &(0..len)
+                .map(|i| ((i * 7) % 11) as f64 / 5.0 - 1.0)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_output_size() {
+        assert_eq!(conv2d(1, 1, 3).output_size(8, 6), (6, 4));
+        assert_eq!(conv2d(1, 1, 3).padding(1).output_size(8, 6), (8, 6));
+        assert_eq!(conv2d(1, 1, 3).stride(2).output_size(8, 6), (3, 2));
+        assert_eq!(conv2d(1, 1, 3).dilation(2).output_size(8, 6), (4, 2));
+        assert_eq!(
+            conv2d(1, 1, 2)
+                .stride(2)
+                .padding(1)
+                .dilation(3)
+                .output_size(7, 7),
+            (3, 3)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_kernel_too_large() {
+        conv2d(1, 1, 3).dilation(3).output_size(6, 6);
+    }
+
+    #[test]
+    fn test_conv2d() {
+        // Two channels, 2x2 kernels summing channel 0 and subtracting
+        // channel 1.
+        let conv = conv2d(2, 1, 2);
+        for (i, w) in conv.w.iter().enumerate() {
+            w.set_data(if i < 4 { 1.0 } else { -1.0 });
+        }
+        conv.b[0].set_data(0.5);
+        let x = Value::vec(&[
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+            1.0, 1.0, 1.0, //
+            1.0, 1.0, 1.0,
+        ]);
+        let out = conv.call(&x, 2, 3);
+        let data: Vec<f64> = out.iter().map(|o| o.get_data()).collect();
+        assert_eq!(data, vec![12.0 - 4.0 + 0.5, 16.0 - 4.0 + 0.5]);
+        assert_eq!(conv.parameters().len(), 2 * 2 * 2 + 1);
+    }
+
+    #[test]
+    fn test_conv2d_padding() {
+        // A 3x3 kernel of ones with padding 1 sums each neighbourhood.
+        let conv = conv2d(1, 1, 3).padding(1);
+        conv.w.iter().for_each(|w| w.set_data(1.0));
+        let x = Value::vec(&[1.0; 9]);
+        let data: Vec<f64> = conv.call(&x, 3, 3).iter().map(|o| o.get_data()).collect();
+        assert_eq!(data, vec![4.0, 6.0, 4.0, 6.0, 9.0, 6.0, 4.0, 6.0, 4.0]);
+    }
+
+    #[test]
+    fn test_conv2d_grad() {
+        for conv in [
+            conv2d(2, 3, 3),
+            conv2d(2, 2, 3).stride(2).padding(1),
+            conv2d(1, 2, 2).dilation(2).padding(2).stride(3),
+        ] {
+            // Batch of two 6x5 maps.
+            let x = input(2 * conv.in_channels * 6 * 5);
+            conv.b.iter().for_each(|b| b.set_data(0.1));
+            let leaves: Vec<&Value> = x.iter().chain(conv.parameters()).collect();
+            check_grad(|| conv.call(&x, 6, 5), &leaves);
+        }
+    }
+
+    #[test]
+    fn test_conv2d_batch() {
+        // Each sample of a batch convolves as it would on its own.
+        let conv = conv2d(2, 3, 2).padding(1);
+        let x = input(3 * 2 * 4 * 3);
+        let out = conv.call(&x, 4, 3);
+        let (oh, ow) = conv.output_size(4, 3);
+        assert_eq!(out.len(), 3 * 3 * oh * ow);
+        for (sample, outs) in x.chunks(2 * 4 * 3).zip(out.chunks(3 * oh * ow)) {
+            let single = conv.call(sample, 4, 3);
+            for (a, b) in outs.iter().zip(single.iter()) {
+                assert_eq!(a.get_data(), b.get_data());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_conv2d_bad_shape() {
+        conv2d(2, 1, 2).call(&input(2 * 3 * 3 + 1), 3, 3);
+    }
+
+    #[test]
+    fn test_conv2d_init() {
+        // One row of `fan_in` weights per output channel, all from the rng.
+        let data = |conv: &Conv2d| -> Vec<f64> {
+            conv.parameters().iter().map(|p| p.get_data()).collect()
+        };
+        let w = Orthogonal(1.0).weights(2 * 3 * 3, 4, &mut StdRng::seed_from_u64(5));
+        let conv = Conv2d::new(2, 4, 3, &Orthogonal(1.0), &mut StdRng::seed_from_u64(5));
+        assert_eq!(data(&conv)[..w.len()], w[..]);
+        assert!(conv.b.iter().all(|b| b.get_data() == 0.0));
+        assert_eq!(data(&conv2d(2, 4, 3)), data(&conv2d(2, 4, 3)));
+        assert_ne!(data(&conv2d(2, 4, 3)), data(&conv));
+    }
+
+    /// 8x8 images of a horizontal (class 0) or vertical (class 1) bar at a
+    /// random position, with noise.
+    fn shapes(n: usize, rng: &mut StdRng) -> Vec<(Vec<f64>, usize)> {
+        (0..n)
+            .map(|i| {
+                let label = i % 2;
+                let pos = rng.gen_range(1..7);
+                let (start, len) = (rng.gen_range(0..3), rng.gen_range(4..6));
+                let mut img: Vec<f64> = (0..64).map(|_| rng.gen_range(-0.1..0.1)).collect();
+                for t in start..start + len {
+                    let (r, c) = if label == 0 { (pos, t) } else { (t, pos) };
+                    img[r * 8 + c] += 1.0;
+                }
+                (img, label)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_train_shapes() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let train = shapes(40, &mut rng);
+        let test = shapes(40, &mut rng);
+
+        // conv 3x3 -> relu -> max pool 2 -> dense to 2 logits.
+        let conv = conv2d(1, 4, 3);
+        let pool = MaxPool2d::new(2);
+        let die = Uniform::from(-0.3..0.3);
+        let head: Vec<Value> = (0..2 * 36)
+            .map(|i| Value::newd(die.sample(&mut rng), format!("h{}", i)))
+            .collect();
+        let logits = |img: &[f64]| -> Vec<Value> {
+            let x = conv.call(&Value::vec(img), 8, 8);
+            let x: Vec<Value> = x.into_iter().map(|v| v.relu()).collect();
+            let x = pool.call(&x, 6, 6);
+            head.chunks(36).map(|w| Value::dot(w, &x)).collect()
+        };
+        let params: Vec<&Value> = conv.parameters().into_iter().chain(head.iter()).collect();
+
+        for _ in 0..30 {
+            let loss = Value::sum_all(
+                &train
+                    .iter()
+                    .map(|(img, label)| -Value::log_softmax(&logits(img))[*label].clone())
+                    .collect::<Vec<_>>(),
+            ) * (1.0 / train.len() as f64);
+            params.iter().for_each(|p| p.zero_grad());
+            loss.backward();
+            for p in params.iter() {
+                p.set_data(p.get_data() - 0.5 * p.get_grad());
+            }
+        }
+
+        let correct = test
+            .iter()
+            .filter(|(img, label)| Value::argmax(&logits(img)) == *label)
+            .count();
+        assert!(correct >= 36, "{} / {} correct", correct, test.len());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod conv1d;
+pub mod conv2d;
+pub mod pool2d;
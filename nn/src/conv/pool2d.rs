@@ -0,0 +1,198 @@
+use crate::mlp::module::Module;
+use crate::tensor::value::Value;
+
+// Both pools slide a square window over every channel of a flat, row-major
+// `[batch, channels, height, width]` feature map and reduce each window to one
+// node. Channels are pooled independently, so the batch and channel
+// dimensions need not be told apart.
+
+fn output_size(kernel_size: usize, stride: usize, height: usize, width: usize) -> (usize, usize) {
+    assert!(
+        height >= kernel_size && width >= kernel_size,
+        "pool: input {}x{} is smaller than the window {}",
+        height,
+        width,
+        kernel_size
+    );
+    (
+        (height - kernel_size) / stride + 1,
+        (width - kernel_size) / stride + 1,
+    )
+}
+
+fn pool(
+    x: &[Value],
+    height: usize,
+    width: usize,
+    kernel_size: usize,
+    stride: usize,
+    reduce: impl Fn(&[Value]) -> Value,
+) -> Vec<Value> {
+    assert!(
+        height * width > 0 && x.len().is_multiple_of(height * width),
+        "pool: {} inputs do not split into {}x{} channels",
+        x.len(),
+        height,
+        width
+    );
+    let channels = x.len() / (height * width);
+    let (oh, ow) = output_size(kernel_size, stride, height, width);
+
+    let mut out = Vec::with_capacity(channels * oh * ow);
+    let mut window = Vec::with_capacity(kernel_size * kernel_size);
+    for c in 0..channels {
+        for i in 0..oh {
+            for j in 0..ow {
+                window.clear();
+                for r in i * stride..i * stride + kernel_size {
+                    let row = (c * height + r) * width;
+                    window.extend_from_slice(&x[row + j * stride..row + j * stride + kernel_size]);
+                }
+                out.push(reduce(&window));
+            }
+        }
+    }
+    out
+}
+
+/// Maximum over each window. The gradient is split evenly between tied
+/// maxima, as in [`Value::maximum`].
+#[derive(Debug)]
+pub struct MaxPool2d {
+    kernel_size: usize,
+    stride: usize,
+}
+
+impl MaxPool2d {
+    /// Non-overlapping windows: the stride defaults to `kernel_size`.
+    pub fn new(kernel_size: usize) -> MaxPool2d {
+        assert!(kernel_size > 0, "MaxPool2d: kernel_size must be positive");
+        MaxPool2d {
+            kernel_size,
+            stride: kernel_size,
+        }
+    }
+
+    pub fn stride(mut self, stride: usize) -> MaxPool2d {
+        assert!(stride > 0, "MaxPool2d: stride must be positive");
+        self.stride = stride;
+        self
+    }
+
+    pub fn output_size(&self, height: usize, width: usize) -> (usize, usize) {
+        output_size(self.kernel_size, self.stride, height, width)
+    }
+
+    pub fn call(&self, x: &[Value], height: usize, width: usize) -> Vec<Value> {
+        pool(
+            x,
+            height,
+            width,
+            self.kernel_size,
+            self.stride,
+            Value::maximum,
+        )
+    }
+}
+
+impl Module for MaxPool2d {}
+
+/// Mean over each window.
+#[derive(Debug)]
+pub struct AvgPool2d {
+    kernel_size: usize,
+    stride: usize,
+}
+
+impl AvgPool2d {
+    /// Non-overlapping windows: the stride defaults to `kernel_size`.
+    pub fn new(kernel_size: usize) -> AvgPool2d {
+        assert!(kernel_size > 0, "AvgPool2d: kernel_size must be positive");
+        AvgPool2d {
+            kernel_size,
+            stride: kernel_size,
+        }
+    }
+
+    pub fn stride(mut self, stride: usize) -> AvgPool2d {
+        assert!(stride > 0, "AvgPool2d: stride must be positive");
+        self.stride = stride;
+        self
+    }
+
+    pub fn output_size(&self, height: usize, width: usize) -> (usize, usize) {
+        output_size(self.kernel_size, self.stride, height, width)
+    }
+
+    pub fn call(&self, x: &[Value], height: usize, width: usize) -> Vec<Value> {
+        let scale = 1.0 / (self.kernel_size * self.kernel_size) as f64;
+        pool(x, height, width, self.kernel_size, self.stride, |w| {
+            Value::sum_all(w) * scale
+        })
+    }
+}
+
+impl Module for AvgPool2d {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_grad;
+
+    // Two 4x4 channels.
+    fn input() -> Vec<Value> {
+        Value::vec(
+            &(0..32)
+                .map(|i| ((i * 5) % 13) as f64 - 6.0 + 0.01 * i as f64)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_max_pool() {
+        let x = Value::vec(&[
+            1.0, 5.0, 2.0, 0.0, //
+            3.0, 4.0, 8.0, 1.0, //
+            0.0, 0.0, 1.0, 1.0, //
+            7.0, 0.0, 1.0, 2.0,
+        ]);
+        let out = MaxPool2d::new(2).call(&x, 4, 4);
+        let data: Vec<f64> = out.iter().map(|o| o.get_data()).collect();
+        assert_eq!(data, vec![5.0, 8.0, 7.0, 2.0]);
+
+        let out = MaxPool2d::new(3).stride(1).call(&x, 4, 4);
+        let data: Vec<f64> = out.iter().map(|o| o.get_data()).collect();
+        assert_eq!(data, vec![8.0; 4]);
+        assert!(MaxPool2d::new(2).parameters().is_empty());
+    }
+
+    #[test]
+    fn test_avg_pool() {
+        let x = Value::vec(&[
+            1.0, 5.0, 2.0, 0.0, //
+            3.0, 4.0, 8.0, 1.0, //
+            0.0, 0.0, 1.0, 1.0, //
+            7.0, 0.0, 1.0, 2.0,
+        ]);
+        let out = AvgPool2d::new(2).call(&x, 4, 4);
+        let data: Vec<f64> = out.iter().map(|o| o.get_data()).collect();
+        assert_eq!(data, vec![3.25, 2.75, 1.75, 1.25]);
+        assert_eq!(AvgPool2d::new(2).stride(1).output_size(4, 4), (3, 3));
+    }
+
+    #[test]
+    fn test_pool_grad() {
+        let x = input();
+        let leaves: Vec<&Value> = x.iter().collect();
+        check_grad(|| MaxPool2d::new(2).call(&x, 4, 4), &leaves);
+        check_grad(|| MaxPool2d::new(3).stride(1).call(&x, 4, 4), &leaves);
+        check_grad(|| AvgPool2d::new(2).call(&x, 4, 4), &leaves);
+        check_grad(|| AvgPool2d::new(3).stride(1).call(&x, 4, 4), &leaves);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pool_bad_shape() {
+        MaxPool2d::new(2).call(&Value::vec(&[1.0; 10]), 3, 3);
+    }
+}
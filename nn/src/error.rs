@@ -0,0 +1,113 @@
+use std::fmt;
+
+/// Errors from validating the inputs of a model and from saving and loading
+/// its state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NnError {
+    /// An input of `got` values given to something taking `expected`.
+    ShapeMismatch { expected: usize, got: usize },
+    /// No input values at all.
+    EmptyInput,
+    /// A NaN or infinite input value at `index`.
+    NonFiniteInput { index: usize, value: f64 },
+    /// A state dict whose keys do not match the model's parameters.
+    StateDictMismatch {
+        missing: Vec<String>,
+        unexpected: Vec<String>,
+    },
+    /// A malformed saved file.
+    Format(String),
+    /// A saved file of a format version this build cannot read.
+    UnsupportedVersion(u32),
+    /// Reading or writing a file failed.
+    Io(String),
+}
+
+impl fmt::Display for NnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NnError::ShapeMismatch { expected, got } => {
+                write!(f, "expected {} inputs, got {}", expected, got)
+            }
+            NnError::EmptyInput => write!(f, "empty input"),
+            NnError::NonFiniteInput { index, value } => {
+                write!(f, "non-finite input {} at index {}", value, index)
+            }
+            NnError::StateDictMismatch {
+                missing,
+                unexpected,
+            } => write!(
+                f,
+                "state dict does not match the model: missing keys [{}], unexpected keys [{}]",
+                missing.join(", "),
+                unexpected.join(", ")
+            ),
+            NnError::Format(msg) => write!(f, "malformed file: {}", msg),
+            NnError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            NnError::Io(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for NnError {}
+
+/// Checks `nin` inputs of the given values: not empty, the right length and
+/// all finite, in that order.
+pub(crate) fn check_input(
+    nin: usize,
+    x: impl ExactSizeIterator<Item = f64>,
+) -> Result<(), NnError> {
+    if x.len() == 0 {
+        return Err(NnError::EmptyInput);
+    }
+    if x.len() != nin {
+        return Err(NnError::ShapeMismatch {
+            expected: nin,
+            got: x.len(),
+        });
+    }
+    match x.enumerate().find(|(_, v)| !v.is_finite()) {
+        Some((index, value)) => Err(NnError::NonFiniteInput { index, value }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_input() {
+        let check = |nin, x: &[f64]| check_input(nin, x.iter().copied());
+        assert_eq!(check(2, &[1.0, 2.0]), Ok(()));
+        assert_eq!(check(2, &[]), Err(NnError::EmptyInput));
+        assert_eq!(
+            check(2, &[1.0, 2.0, 3.0]),
+            Err(NnError::ShapeMismatch {
+                expected: 2,
+                got: 3
+            })
+        );
+        assert_eq!(
+            check(3, &[1.0, f64::INFINITY, f64::NAN]),
+            Err(NnError::NonFiniteInput {
+                index: 1,
+                value: f64::INFINITY
+            })
+        );
+        assert!(matches!(
+            check(1, &[f64::NAN]),
+            Err(NnError::NonFiniteInput { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        let err = NnError::ShapeMismatch {
+            expected: 4,
+            got: 3,
+        };
+        assert_eq!(err.to_string(), "expected 4 inputs, got 3");
+        assert_eq!(NnError::EmptyInput.to_string(), "empty input");
+    }
+}
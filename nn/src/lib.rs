@@ -1,3 +1,6 @@
+pub mod conv;
+pub mod error;
 pub mod mlp;
 pub mod ops;
 pub mod tensor;
+pub mod train;
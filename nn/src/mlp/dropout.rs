@@ -0,0 +1,161 @@
+use crate::mlp::module::{Forward, Module};
+use crate::tensor::value::Value;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::{Cell, RefCell};
+
+/// Inverted dropout: in training mode zeroes each input with probability `p`
+/// and scales the rest by `1 / (1 - p)`, so the expected output equals the
+/// input. In eval mode it passes inputs through unchanged.
+#[derive(Debug)]
+pub struct Dropout {
+    p: f64,
+    rng: RefCell<StdRng>,
+    training: Cell<bool>,
+}
+
+impl Dropout {
+    /// Masks drawn from an entropy-seeded RNG.
+    pub fn new(p: f64) -> Dropout {
+        assert!(
+            (0.0..1.0).contains(&p),
+            "Dropout: p must be in [0, 1), got {}",
+            p
+        );
+        Dropout {
+            p,
+            rng: RefCell::new(StdRng::from_entropy()),
+            training: Cell::new(true),
+        }
+    }
+
+    /// Reseeds the mask RNG, for reproducible masks.
+    pub fn seed(self, seed: u64) -> Dropout {
+        self.rng.replace(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+
+    /// The factor for each of the next `n` inputs, `0` or `1 / (1 - p)`, or
+    /// `None` in eval mode. Masks are drawn in input order, so one call for a
+    /// whole batch gives the same masks as one call per sample.
+    pub(crate) fn mask(&self, n: usize) -> Option<Vec<f64>> {
+        if !self.training.get() {
+            return None;
+        }
+        let scale = 1.0 / (1.0 - self.p);
+        let mut rng = self.rng.borrow_mut();
+        Some(
+            (0..n)
+                .map(|_| {
+                    if rng.gen::<f64>() >= self.p {
+                        scale
+                    } else {
+                        0.0
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Module for Dropout {
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+}
+
+impl Forward for Dropout {
+    fn forward(&self, x: Vec<Value>) -> Vec<Value> {
+        match self.mask(x.len()) {
+            Some(mask) => x.into_iter().zip(mask).map(|(x, m)| x * m).collect(),
+            None => x,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dropout() {
+        let dropout = Dropout::new(0.5).seed(0);
+        let x = Value::vec(&[1.0; 1000]);
+        let y = dropout.forward(x.clone());
+        let kept = y.iter().filter(|y| y.get_data() != 0.0).count();
+        assert!((400..600).contains(&kept), "kept {}", kept);
+        assert!(y.iter().all(|y| y.get_data() == 0.0 || y.get_data() == 2.0));
+
+        // Gradients pass through kept inputs only, with the same scale.
+        Value::sum_all(&y).backward();
+        for (x, y) in x.iter().zip(&y) {
+            assert_eq!(x.get_grad(), y.get_data());
+        }
+    }
+
+    #[test]
+    fn test_dropout_seed() {
+        let mask = |d: &Dropout| -> Vec<f64> {
+            d.forward(Value::vec(&[1.0; 64]))
+                .iter()
+                .map(|y| y.get_data())
+                .collect()
+        };
+        assert_eq!(
+            mask(&Dropout::new(0.3).seed(9)),
+            mask(&Dropout::new(0.3).seed(9))
+        );
+        let dropout = Dropout::new(0.3).seed(9);
+        assert_ne!(mask(&dropout), mask(&dropout));
+        assert_eq!(mask(&Dropout::new(0.0)), vec![1.0; 64]);
+    }
+
+    #[test]
+    fn test_dropout_eval() {
+        let dropout = Dropout::new(0.5).seed(0);
+        assert!(dropout.is_training());
+        dropout.eval();
+        assert!(!dropout.is_training());
+        let x = Value::vec(&[0.5, -1.0, 3.0]);
+        let y = dropout.forward(x.clone());
+        assert_eq!(
+            y.iter().map(Value::get_data).collect::<Vec<_>>(),
+            [0.5, -1.0, 3.0]
+        );
+        // The very same nodes: eval adds nothing to the graph.
+        Value::sum_all(&y).backward();
+        assert!(x.iter().all(|x| x.get_grad() == 1.0));
+
+        // Back in training the mask RNG carries on where it was.
+        dropout.train();
+        let masked = dropout.forward(Value::vec(&[1.0; 64]));
+        assert!(masked.iter().any(|y| y.get_data() == 0.0));
+    }
+
+    #[test]
+    fn test_dropout_scaling() {
+        // Kept activations are scaled by 1 / (1 - p), so the mean survives.
+        let dropout = Dropout::new(0.25).seed(5);
+        let y = dropout.forward(Value::vec(&[3.0; 20_000]));
+        let scaled = 3.0 / 0.75;
+        assert!(y
+            .iter()
+            .all(|y| y.get_data() == 0.0 || y.get_data() == scaled));
+        let mean = y.iter().map(Value::get_data).sum::<f64>() / y.len() as f64;
+        assert!((mean - 3.0).abs() < 0.05, "mean {}", mean);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dropout_p() {
+        Dropout::new(1.0);
+    }
+}
@@ -0,0 +1,246 @@
+use rand::distributions::{Distribution, Uniform as UniformDist};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+/// Weight initialization scheme for a dense layer.
+///
+/// A layer of `fan_out` neurons over `fan_in` inputs asks for its whole
+/// `fan_out x fan_in` weight matrix at once, row-major with one row per
+/// neuron, so schemes like [`Orthogonal`] can couple the rows. All randomness
+/// comes from the `rng` passed in: the same seed gives the same weights.
+///
+/// Any `Fn(fan_in, fan_out, rng) -> f64` closure is an initializer drawing
+/// each weight independently.
+pub trait Initializer {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64>;
+
+    /// The `fan_out` biases, zero unless overridden.
+    fn biases(&self, _fan_in: usize, fan_out: usize, _rng: &mut dyn RngCore) -> Vec<f64> {
+        vec![0.0; fan_out]
+    }
+}
+
+impl<F: Fn(usize, usize, &mut dyn RngCore) -> f64> Initializer for F {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        (0..fan_in * fan_out)
+            .map(|_| self(fan_in, fan_out, rng))
+            .collect()
+    }
+}
+
+fn uniform(bound: f64, n: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+    let die = UniformDist::from(-bound..bound);
+    (0..n).map(|_| die.sample(rng)).collect()
+}
+
+/// A standard normal sample, by Box-Muller.
+fn standard_normal(rng: &mut dyn RngCore) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+fn normal(std: f64, n: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+    (0..n).map(|_| std * standard_normal(rng)).collect()
+}
+
+/// Uniform on `[low, high)`. `Uniform(-1.0, 1.0)` is the default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Uniform(pub f64, pub f64);
+
+impl Initializer for Uniform {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        let die = UniformDist::from(self.0..self.1);
+        (0..fan_in * fan_out).map(|_| die.sample(rng)).collect()
+    }
+}
+
+/// Every weight and bias set to the same value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constant(pub f64);
+
+impl Initializer for Constant {
+    fn weights(&self, fan_in: usize, fan_out: usize, _rng: &mut dyn RngCore) -> Vec<f64> {
+        vec![self.0; fan_in * fan_out]
+    }
+
+    fn biases(&self, _fan_in: usize, fan_out: usize, _rng: &mut dyn RngCore) -> Vec<f64> {
+        vec![self.0; fan_out]
+    }
+}
+
+/// Xavier/Glorot uniform: `±sqrt(6 / (fan_in + fan_out))`, keeping the
+/// variance of activations and gradients level through tanh/sigmoid layers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XavierUniform;
+
+impl Initializer for XavierUniform {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        let bound = (6.0 / (fan_in + fan_out) as f64).sqrt();
+        uniform(bound, fan_in * fan_out, rng)
+    }
+}
+
+/// Xavier/Glorot normal: standard deviation `sqrt(2 / (fan_in + fan_out))`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XavierNormal;
+
+impl Initializer for XavierNormal {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        let std = (2.0 / (fan_in + fan_out) as f64).sqrt();
+        normal(std, fan_in * fan_out, rng)
+    }
+}
+
+/// Kaiming/He uniform: `±sqrt(6 / fan_in)`, for ReLU layers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KaimingUniform;
+
+impl Initializer for KaimingUniform {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        let bound = (6.0 / fan_in as f64).sqrt();
+        uniform(bound, fan_in * fan_out, rng)
+    }
+}
+
+/// Kaiming/He normal: standard deviation `sqrt(2 / fan_in)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KaimingNormal;
+
+impl Initializer for KaimingNormal {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        let std = (2.0 / fan_in as f64).sqrt();
+        normal(std, fan_in * fan_out, rng)
+    }
+}
+
+/// A random (semi-)orthogonal matrix scaled by the gain: orthonormal rows
+/// when `fan_out <= fan_in`, orthonormal columns otherwise. Built by
+/// Gram-Schmidt on a Gaussian matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orthogonal(pub f64);
+
+impl Initializer for Orthogonal {
+    fn weights(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        // Orthonormalize the `rows` shorter vectors of length `cols`.
+        let (rows, cols) = (fan_in.min(fan_out), fan_in.max(fan_out));
+        let mut q: Vec<Vec<f64>> = Vec::with_capacity(rows);
+        while q.len() < rows {
+            let mut v = normal(1.0, cols, rng);
+            for u in q.iter() {
+                let d: f64 = u.iter().zip(&v).map(|(a, b)| a * b).sum();
+                v.iter_mut().zip(u).for_each(|(a, b)| *a -= d * b);
+            }
+            let norm = v.iter().map(|a| a * a).sum::<f64>().sqrt();
+            // A draw (numerically) in the span of the previous rows: redraw.
+            if norm > 1e-6 {
+                q.push(v.iter().map(|a| a / norm).collect());
+            }
+        }
+        (0..fan_out)
+            .flat_map(|o| {
+                let q = &q;
+                (0..fan_in).map(move |i| {
+                    let x = if fan_out <= fan_in { q[o][i] } else { q[i][o] };
+                    self.0 * x
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn moments(xs: &[f64]) -> (f64, f64) {
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        (mean, var)
+    }
+
+    #[test]
+    fn test_variance() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (fan_in, fan_out) = (200, 100);
+        let schemes: [(&dyn Initializer, f64); 5] = [
+            (&Uniform(-1.0, 1.0), 1.0 / 3.0),
+            (&XavierUniform, 2.0 / 300.0),
+            (&XavierNormal, 2.0 / 300.0),
+            (&KaimingUniform, 2.0 / 200.0),
+            (&KaimingNormal, 2.0 / 200.0),
+        ];
+        for (init, expected) in schemes {
+            let w = init.weights(fan_in, fan_out, &mut rng);
+            assert_eq!(w.len(), fan_in * fan_out);
+            let (mean, var) = moments(&w);
+            assert!(mean.abs() < 0.05 * expected.sqrt());
+            assert!(
+                (var / expected - 1.0).abs() < 0.03,
+                "{} vs {}",
+                var,
+                expected
+            );
+            assert_eq!(init.biases(fan_in, fan_out, &mut rng), vec![0.0; fan_out]);
+        }
+    }
+
+    #[test]
+    fn test_uniform_bounds() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let bound = (6.0 / 30.0f64).sqrt();
+        let w = XavierUniform.weights(10, 20, &mut rng);
+        assert!(w.iter().all(|w| w.abs() < bound));
+        let w = Uniform(2.0, 3.0).weights(10, 20, &mut rng);
+        assert!(w.iter().all(|w| (2.0..3.0).contains(w)));
+    }
+
+    #[test]
+    fn test_constant() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(Constant(0.5).weights(3, 2, &mut rng), vec![0.5; 6]);
+        assert_eq!(Constant(0.5).biases(3, 2, &mut rng), vec![0.5; 2]);
+    }
+
+    #[test]
+    fn test_orthogonal() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for (fan_in, fan_out) in [(6, 4), (4, 6), (5, 5)] {
+            let w = Orthogonal(2.0).weights(fan_in, fan_out, &mut rng);
+            // Gram matrix of the shorter side is gain^2 * I.
+            let (rows, cols) = (fan_in.min(fan_out), fan_in.max(fan_out));
+            let at = |r: usize, c: usize| {
+                if fan_out <= fan_in {
+                    w[r * fan_in + c]
+                } else {
+                    w[c * fan_in + r]
+                }
+            };
+            for a in 0..rows {
+                for b in 0..rows {
+                    let d: f64 = (0..cols).map(|c| at(a, c) * at(b, c)).sum();
+                    let expected = if a == b { 4.0 } else { 0.0 };
+                    assert!((d - expected).abs() < 1e-12, "{} {} {}", a, b, d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_closure() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let init = |fan_in: usize, _: usize, _: &mut dyn RngCore| 1.0 / fan_in as f64;
+        assert_eq!(init.weights(4, 2, &mut rng), vec![0.25; 8]);
+        assert_eq!(init.biases(4, 2, &mut rng), vec![0.0; 2]);
+    }
+
+    #[test]
+    fn test_seeded() {
+        let draw = |seed| KaimingNormal.weights(8, 8, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(draw(5), draw(5));
+        assert_ne!(draw(5), draw(6));
+    }
+}
@@ -1,36 +1,164 @@
-use crate::mlp::module::Module;
+use crate::error::{check_input, NnError};
+use crate::mlp::dropout::Dropout;
+use crate::mlp::init::{Initializer, Uniform};
+use crate::mlp::module::{Forward, Module};
 use crate::mlp::neuron::Neuron;
+use crate::ops::activation::Activation;
+use crate::tensor::tensor::Tensor;
 use crate::tensor::value::Value;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cell::Cell;
 use std::rc::Rc;
 
 pub struct Layer {
+    nin: usize,
+    act: Activation,
     neurons: Rc<Vec<Neuron>>,
+    dropout: Option<Dropout>,
+    training: Cell<bool>,
 }
 
 impl Layer {
-    pub fn new(nin: usize, nout: usize, nonlin: bool) -> Layer {
+    /// `nout` neurons over `nin` inputs, with weights drawn from
+    /// `Uniform(-1.0, 1.0)` by an entropy-seeded RNG, as in [`MLP::new`].
+    ///
+    /// [`MLP::new`]: crate::mlp::mlp::MLP::new
+    pub fn new(nin: usize, nout: usize, act: Activation) -> Layer {
+        Layer::with_init(
+            nin,
+            nout,
+            act,
+            &Uniform(-1.0, 1.0),
+            &mut StdRng::from_entropy(),
+        )
+    }
+
+    /// `nout` neurons over `nin` inputs, with weights and biases drawn by
+    /// `init` from `rng`.
+    pub fn with_init(
+        nin: usize,
+        nout: usize,
+        act: Activation,
+        init: &dyn Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Layer {
+        let w = init.weights(nin, nout, rng);
+        let b = init.biases(nin, nout, rng);
+        let neurons = b
+            .iter()
+            .enumerate()
+            .map(|(o, &b)| Neuron::new(&w[o * nin..(o + 1) * nin], b, act))
+            .collect();
         Layer {
-            neurons: Rc::new(
-                (0..nout)
-                    .map(|l| Neuron::new(nin, nonlin, l as f64 + 3.0))
-                    .collect(),
-            ),
+            nin,
+            act,
+            neurons: Rc::new(neurons),
+            dropout: None,
+            training: Cell::new(true),
         }
     }
 
-    pub fn call(&self, x: &Vec<Value>) -> Vec<Value> {
-        self.neurons.iter().map(|n| n.call(x.to_owned())).collect()
+    /// Applies `dropout` to the layer's outputs, after the activation.
+    pub fn with_dropout(mut self, dropout: Dropout) -> Layer {
+        dropout.set_training(self.training.get());
+        self.dropout = Some(dropout);
+        self
+    }
+
+    pub fn nin(&self) -> usize {
+        self.nin
+    }
+
+    /// Every neuron's output, after checking `x` against the layer's inputs.
+    pub fn call(&self, x: &[Value]) -> Result<Vec<Value>, NnError> {
+        check_input(self.nin, x.iter().map(Value::get_data))?;
+        Ok(self.call_unchecked(x))
+    }
+
+    /// [`call`](Layer::call) without the checks. Panics on a length
+    /// mismatch.
+    pub fn call_unchecked(&self, x: &[Value]) -> Vec<Value> {
+        let y = self.neurons.iter().map(|n| n.call_unchecked(x)).collect();
+        match &self.dropout {
+            Some(dropout) => dropout.forward(y),
+            None => y,
+        }
+    }
+
+    /// The layer applied to a `[batch, nin]` tensor as `x @ w^T + b`, one
+    /// matmul for the whole batch. The weights enter the graph through
+    /// [`Tensor::from_values`], so backpropagating through the result reaches
+    /// the layer's parameters. In training mode, dropout masks are drawn for
+    /// the whole batch in sample order, the same masks per-sample calls would
+    /// get.
+    pub fn call_tensor(&self, x: Tensor) -> Tensor {
+        let (nin, nout) = (self.nin, self.neurons.len());
+        // One row of weights per neuron: w is [nout, nin].
+        let w: Vec<Value> = self
+            .neurons
+            .iter()
+            .flat_map(|n| n.weights().iter().cloned())
+            .collect();
+        let b: Vec<Value> = self.neurons.iter().map(|n| n.bias().clone()).collect();
+        let w = Tensor::from_values(&w, &[nout, nin]).transpose(0, 1);
+        let y = x.matmul(w) + Tensor::from_values(&b, &[nout]);
+        let y = self.act.apply_tensor(y);
+        match self.dropout.as_ref().and_then(|d| d.mask(y.numel())) {
+            Some(mask) => {
+                let shape = y.shape().to_vec();
+                y * Tensor::newd(mask, &shape, "mask".to_string())
+            }
+            None => y,
+        }
     }
 }
+
 impl Module for Layer {
     fn parameters(&self) -> Vec<&Value> {
         self.neurons.iter().flat_map(Module::parameters).collect()
     }
+
+    fn named_children(&self) -> Vec<(String, &dyn Module)> {
+        self.neurons
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (format!("neurons.{}", i), n as &dyn Module))
+            .chain(
+                self.dropout
+                    .iter()
+                    .map(|d| ("dropout".to_string(), d as &dyn Module)),
+            )
+            .collect()
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+        for child in self.children() {
+            child.set_training(training);
+        }
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+}
+
+/// [`Layer::call_unchecked`].
+impl Forward for Layer {
+    fn forward(&self, x: Vec<Value>) -> Vec<Value> {
+        self.call_unchecked(&x)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::mlp::init::Orthogonal;
+
+    fn layer(nin: usize, nout: usize, act: Activation) -> Layer {
+        Layer::new(nin, nout, act)
+    }
 
     fn print_value(value: &Value) {
         println!(
@@ -47,8 +175,8 @@ mod test {
     }
     #[test]
     fn test_layer() {
-        let layer = Layer::new(4, 5, true);
-        let output = layer.call(&Value::vec(&[1.0, -2.0, 3.0]));
+        let layer = layer(4, 5, Activation::Relu);
+        let output = layer.call(&Value::vec(&[1.0, -2.0, 3.0, 0.5])).unwrap();
         println!("layer: {:#?}", layer.neurons);
         for o in output.iter() {
             o.backward();
@@ -59,10 +187,22 @@ mod test {
         assert_eq!(params.len(), 4 * 5 + 5);
     }
 
+    #[test]
+    fn test_layer_activation() {
+        let layer = layer(3, 4, Activation::Tanh);
+        let output = layer.call(&Value::vec(&[1.0, -2.0, 3.0])).unwrap();
+        assert_eq!(output.len(), 4);
+        for o in output.iter() {
+            assert!(o.get_data().abs() < 1.0);
+            assert_eq!(o._prev.len(), 1);
+        }
+    }
+
     #[test]
     fn test_layer_large() {
-        let layer = Layer::new(10, 10, true);
-        let output = layer.call(&Value::vec(&[1.0, -2.0, 3.0]));
+        let layer = layer(10, 10, Activation::Relu);
+        let x: Vec<f64> = (0..10).map(|i| i as f64 - 4.5).collect();
+        let output = layer.call(&Value::vec(&x)).unwrap();
         println!("layer: {:#?}", layer.neurons);
         for o in output.iter() {
             o.backward();
@@ -72,4 +212,166 @@ mod test {
         print_params(&params);
         assert_eq!(params.len(), 10 * 10 + 10);
     }
+
+    #[test]
+    fn test_layer_new() {
+        let layer = Layer::new(4, 3, Activation::Tanh);
+        assert_eq!(layer.nin(), 4);
+        assert_eq!(layer.parameters().len(), 3 * 5);
+        assert!(layer
+            .parameters()
+            .iter()
+            .all(|p| (-1.0..1.0).contains(&p.get_data())));
+        assert_eq!(layer.call_unchecked(&Value::vec(&[0.0; 4])).len(), 3);
+    }
+
+    #[test]
+    fn test_layer_init() {
+        // Rows of the orthogonal matrix land on the neurons in order.
+        let mut rng = StdRng::seed_from_u64(0);
+        let w = Orthogonal(1.0).weights(5, 3, &mut rng);
+        let mut rng = StdRng::seed_from_u64(0);
+        let layer = Layer::with_init(5, 3, Activation::Identity, &Orthogonal(1.0), &mut rng);
+        let params: Vec<f64> = layer.parameters().iter().map(|p| p.get_data()).collect();
+        for o in 0..3 {
+            assert_eq!(params[o * 6..o * 6 + 5], w[o * 5..o * 5 + 5]);
+            assert_eq!(params[o * 6 + 5], 0.0);
+        }
+    }
+
+    #[test]
+    fn test_layer_checked() {
+        let layer = layer(4, 5, Activation::Relu);
+        assert_eq!(
+            layer.call(&Value::vec(&[1.0, -2.0, 3.0])).unwrap_err(),
+            NnError::ShapeMismatch {
+                expected: 4,
+                got: 3
+            }
+        );
+        assert_eq!(layer.call(&[]).unwrap_err(), NnError::EmptyInput);
+        assert!(matches!(
+            layer.call(&Value::vec(&[1.0, 2.0, 3.0, f64::NEG_INFINITY])),
+            Err(NnError::NonFiniteInput { index: 3, .. })
+        ));
+        let x = Value::vec(&[1.0, -2.0, 3.0, 0.5]);
+        let checked: Vec<f64> = layer
+            .call(&x)
+            .unwrap()
+            .iter()
+            .map(Value::get_data)
+            .collect();
+        let unchecked: Vec<f64> = layer
+            .call_unchecked(&x)
+            .iter()
+            .map(Value::get_data)
+            .collect();
+        assert_eq!(checked, unchecked);
+    }
+
+    #[test]
+    fn test_layer_tensor() {
+        for act in [
+            Activation::Identity,
+            Activation::Relu,
+            Activation::Tanh,
+            Activation::LeakyRelu(0.1),
+            Activation::Sigmoid,
+            Activation::Gelu,
+            Activation::Silu,
+        ] {
+            let layer = layer(3, 4, act);
+            let xs = [[1.0, -2.0, 3.0], [0.5, 0.25, -1.0]];
+            let y = layer.call_tensor(Tensor::newd(xs.concat(), &[2, 3], "x".to_string()));
+            assert_eq!(y.shape(), &[2, 4]);
+            let expected: Vec<f64> = xs
+                .iter()
+                .flat_map(|x| layer.call_unchecked(&Value::vec(x)))
+                .map(|v| v.get_data())
+                .collect();
+            for (a, b) in y.get_data().iter().zip(&expected) {
+                assert!((a - b).abs() < 1e-12, "{:?}: {} vs {}", act, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_layer_tensor_grad() {
+        // Backward through the matmul path gives the per-sample gradients.
+        let layer = layer(3, 4, Activation::Tanh);
+        let xs = [[1.0, -2.0, 3.0], [0.5, 0.25, -1.0]];
+        let y = layer.call_tensor(Tensor::newd(xs.concat(), &[2, 3], "x".to_string()));
+        y.sum().backward();
+        let tensor: Vec<f64> = layer.parameters().iter().map(|p| p.get_grad()).collect();
+
+        layer.zero_grad();
+        let outs: Vec<Value> = xs
+            .iter()
+            .flat_map(|x| layer.call_unchecked(&Value::vec(x)))
+            .collect();
+        Value::sum_all(&outs).backward();
+        for (p, t) in layer.parameters().iter().zip(&tensor) {
+            assert!(
+                (p.get_grad() - t).abs() < 1e-12,
+                "{} vs {}",
+                p.get_grad(),
+                t
+            );
+        }
+    }
+
+    #[test]
+    fn test_layer_dropout() {
+        let layer = layer(3, 64, Activation::Identity).with_dropout(Dropout::new(0.5).seed(2));
+        let x = Value::vec(&[1.0, -2.0, 3.0]);
+        let dense: Vec<f64> = layer
+            .neurons
+            .iter()
+            .map(|n| n.call_unchecked(&x).get_data())
+            .collect();
+        let y = layer.call_unchecked(&x);
+        assert!(y.iter().any(|y| y.get_data() == 0.0));
+        for (y, d) in y.iter().zip(&dense) {
+            assert!(y.get_data() == 0.0 || y.get_data() == 2.0 * d);
+        }
+        // Dropout holds no parameters, so the names are unchanged.
+        assert_eq!(layer.named_parameters().len(), 64 * 4);
+        assert_eq!(layer.named_children().last().unwrap().0, "dropout");
+
+        layer.eval();
+        assert!(!layer.is_training());
+        assert!(!layer.named_children().last().unwrap().1.is_training());
+        let y: Vec<f64> = layer
+            .call_unchecked(&x)
+            .iter()
+            .map(Value::get_data)
+            .collect();
+        assert_eq!(y, dense);
+        let t = layer.call_tensor(Tensor::newd(vec![1.0, -2.0, 3.0], &[1, 3], "x".to_string()));
+        for (a, b) in t.get_data().iter().zip(&dense) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_layer_tensor_dropout() {
+        // In training mode the batch draws the masks per-sample calls would.
+        let seeded = || {
+            let mut rng = StdRng::seed_from_u64(4);
+            Layer::with_init(3, 8, Activation::Relu, &Uniform(-1.0, 1.0), &mut rng)
+                .with_dropout(Dropout::new(0.5).seed(9))
+        };
+        let (batched, single) = (seeded(), seeded());
+        let xs = [[1.0, -2.0, 3.0], [0.5, 0.25, -1.0], [2.0, 1.0, 0.0]];
+        let y = batched.call_tensor(Tensor::newd(xs.concat(), &[3, 3], "x".to_string()));
+        let expected: Vec<f64> = xs
+            .iter()
+            .flat_map(|x| single.call_unchecked(&Value::vec(x)))
+            .map(|v| v.get_data())
+            .collect();
+        assert!(expected.contains(&0.0));
+        for (a, b) in y.get_data().iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-12, "{} vs {}", a, b);
+        }
+    }
 }
@@ -1,66 +1,328 @@
+use crate::error::{check_input, NnError};
+use crate::mlp::dropout::Dropout;
+use crate::mlp::init::{Initializer, Uniform};
 use crate::mlp::layer::Layer;
-use crate::mlp::module::Module;
+use crate::mlp::module::{Forward, Module};
+use crate::ops::activation::Activation;
+use crate::tensor::tensor::Tensor;
 use crate::tensor::value::Value;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cell::Cell;
 use std::rc::Rc;
 
 pub struct MLP {
     layers: Rc<Vec<Layer>>,
+    training: Cell<bool>,
 }
 
 impl MLP {
+    /// ReLU hidden layers and a linear output layer.
     pub fn new(nin: usize, nouts: &[usize]) -> MLP {
-        let sz = [nin]
+        MLP::with_activation(nin, nouts, Activation::Relu)
+    }
+
+    /// `act` on every hidden layer and a linear output layer.
+    pub fn with_activation(nin: usize, nouts: &[usize], act: Activation) -> MLP {
+        nouts
             .iter()
-            .chain(nouts.iter())
-            .cloned()
-            .collect::<Vec<_>>();
-        let layers = Rc::new(
-            (0..nouts.len())
-                .map(|i| Layer::new(sz[i], sz[i + 1], i != nouts.len() - 1))
-                .collect(),
-        );
-        MLP { layers }
+            .enumerate()
+            .fold(MLP::builder(nin), |builder, (i, &nout)| {
+                let last = i == nouts.len() - 1;
+                builder.dense(nout, if last { Activation::Identity } else { act })
+            })
+            .build()
     }
-    pub fn call(&self, x: &[f64]) -> Value {
+
+    /// Starts an empty model taking `nin` inputs, to be stacked layer by
+    /// layer with [`MLPBuilder::dense`].
+    pub fn builder(nin: usize) -> MLPBuilder {
+        MLPBuilder {
+            nin,
+            layers: vec![],
+            init: Box::new(Uniform(-1.0, 1.0)),
+            seed: None,
+        }
+    }
+
+    /// Every output of the final layer, after checking `x` against the
+    /// model's inputs.
+    pub fn forward(&self, x: &[f64]) -> Result<Vec<Value>, NnError> {
+        // Later layers get exactly what the one before produced.
+        let mut y = self.layers[0].call(&Value::vec(x))?;
+        for layer in self.layers[1..].iter() {
+            y = layer.call_unchecked(&y);
+        }
+        Ok(y)
+    }
+
+    /// [`forward`](MLP::forward) without the checks. Panics on a length
+    /// mismatch.
+    pub fn forward_unchecked(&self, x: &[f64]) -> Vec<Value> {
         let mut y = Value::vec(x);
         for layer in self.layers.iter() {
-            y = layer.call(&y);
+            y = layer.call_unchecked(&y);
+        }
+        y
+    }
+
+    /// [`forward`](MLP::forward) over a batch, checking every sample before
+    /// building any graph. All samples share the model's weight leaves, so a
+    /// loss over the batch backpropagates into them once; each output is
+    /// identical to the per-sample `forward`. For one matmul per layer over
+    /// the whole batch, use [`forward_tensor`](MLP::forward_tensor).
+    pub fn forward_batch(&self, xs: &[Vec<f64>]) -> Result<Vec<Vec<Value>>, NnError> {
+        let nin = self.layers[0].nin();
+        for x in xs {
+            check_input(nin, x.iter().copied())?;
         }
-        y[0].clone()
+        Ok(xs.iter().map(|x| self.forward_unchecked(x)).collect())
     }
+
+    /// The model on a `[batch, nin]` tensor, one matmul per layer, giving
+    /// `[batch, nout]`. Results match [`forward_batch`](MLP::forward_batch)
+    /// up to summation order, and backpropagating from them reaches the
+    /// model's parameters.
+    pub fn forward_tensor(&self, x: &Tensor) -> Result<Tensor, NnError> {
+        let nin = self.layers[0].nin();
+        match x.shape() {
+            [_, n] if *n == nin => {}
+            shape => {
+                return Err(NnError::ShapeMismatch {
+                    expected: nin,
+                    got: shape.last().copied().unwrap_or(0),
+                })
+            }
+        }
+        let data = x.get_data();
+        check_input(data.len(), data.into_iter())?;
+        Ok(self
+            .layers
+            .iter()
+            .fold(x.clone(), |y, layer| layer.call_tensor(y)))
+    }
+
+    /// The output of a single-output model, after checking `x` against the
+    /// model's inputs. Panics if the final layer has more than one neuron;
+    /// use [`forward`](MLP::forward) for those.
+    pub fn call(&self, x: &[f64]) -> Result<Value, NnError> {
+        self.forward(x).map(MLP::single)
+    }
+
+    /// [`call`](MLP::call) without the checks. Panics on a length mismatch.
+    pub fn call_unchecked(&self, x: &[f64]) -> Value {
+        MLP::single(self.forward_unchecked(x))
+    }
+
+    fn single(mut y: Vec<Value>) -> Value {
+        assert_eq!(
+            y.len(),
+            1,
+            "MLP::call on a model with {} outputs, use MLP::forward",
+            y.len()
+        );
+        y.remove(0)
+    }
+}
+
+/// Layer-by-layer construction of an [`MLP`], each layer with its own
+/// activation and optionally its own initializer:
+///
+/// ```
+/// use nn::mlp::init::{KaimingNormal, XavierUniform};
+/// use nn::mlp::mlp::MLP;
+/// use nn::ops::activation::Activation::{Gelu, Identity, Tanh};
+///
+/// let model = MLP::builder(2)
+///     .init(XavierUniform)
+///     .seed(42)
+///     .dense(16, Tanh)
+///     .dropout(0.1)
+///     .dense_with(16, Gelu, KaimingNormal)
+///     .dense(1, Identity)
+///     .build();
+/// ```
+///
+/// Layers are only created by [`build`](MLPBuilder::build), in order, so the
+/// same seed always gives bit-identical models, and the same dropout masks.
+pub struct MLPBuilder {
+    nin: usize,
+    layers: Vec<Dense>,
+    init: Box<dyn Initializer>,
+    seed: Option<u64>,
 }
+
+/// A layer still to be built.
+struct Dense {
+    nout: usize,
+    act: Activation,
+    init: Option<Box<dyn Initializer>>,
+    dropout: Option<f64>,
+}
+
+impl MLPBuilder {
+    /// Appends a fully connected layer of `nout` neurons.
+    pub fn dense(mut self, nout: usize, act: Activation) -> MLPBuilder {
+        self.layers.push(Dense {
+            nout,
+            act,
+            init: None,
+            dropout: None,
+        });
+        self
+    }
+
+    /// Appends a fully connected layer initialized by `init` rather than the
+    /// model-wide initializer.
+    pub fn dense_with(
+        mut self,
+        nout: usize,
+        act: Activation,
+        init: impl Initializer + 'static,
+    ) -> MLPBuilder {
+        self.layers.push(Dense {
+            nout,
+            act,
+            init: Some(Box::new(init)),
+            dropout: None,
+        });
+        self
+    }
+
+    /// Drops the outputs of the last layer added with probability `p` while
+    /// training. See [`Dropout`].
+    pub fn dropout(mut self, p: f64) -> MLPBuilder {
+        let dense = self
+            .layers
+            .last_mut()
+            .expect("MLPBuilder::dropout: no layer to apply it to");
+        assert!(
+            (0.0..1.0).contains(&p),
+            "Dropout: p must be in [0, 1), got {}",
+            p
+        );
+        dense.dropout = Some(p);
+        self
+    }
+
+    /// Initializer for layers without their own, `Uniform(-1.0, 1.0)` by
+    /// default.
+    pub fn init(mut self, init: impl Initializer + 'static) -> MLPBuilder {
+        self.init = Box::new(init);
+        self
+    }
+
+    /// Seeds the `StdRng` that [`build`](MLPBuilder::build) draws from,
+    /// which is otherwise seeded from entropy.
+    pub fn seed(mut self, seed: u64) -> MLPBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> MLP {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        self.build_with_rng(&mut rng)
+    }
+
+    /// Builds drawing every weight from `rng`, ignoring any seed. The
+    /// dropout masks are seeded from `rng` after all the weights, so adding
+    /// dropout leaves the weights unchanged.
+    pub fn build_with_rng(self, rng: &mut dyn RngCore) -> MLP {
+        assert!(!self.layers.is_empty(), "MLP: no layers");
+        let mut nin = self.nin;
+        let layers: Vec<Layer> = self
+            .layers
+            .iter()
+            .map(|dense| {
+                let init = dense.init.as_deref().unwrap_or(self.init.as_ref());
+                let layer = Layer::with_init(nin, dense.nout, dense.act, init, rng);
+                nin = dense.nout;
+                layer
+            })
+            .collect();
+        let layers = layers
+            .into_iter()
+            .zip(&self.layers)
+            .map(|(layer, dense)| match dense.dropout {
+                Some(p) => layer.with_dropout(Dropout::new(p).seed(rng.next_u64())),
+                None => layer,
+            })
+            .collect();
+        MLP {
+            layers: Rc::new(layers),
+            training: Cell::new(true),
+        }
+    }
+}
+
 impl Module for MLP {
     fn parameters(&self) -> Vec<&Value> {
         self.layers.iter().flat_map(Module::parameters).collect()
     }
-}
 
-fn print_value(value: &Value) {
-    println!(
-        "param: label:{:?}, data:{:?},grad:{:?}",
-        value.get_label(),
-        value.get_data(),
-        value.get_grad()
-    );
+    fn named_children(&self) -> Vec<(String, &dyn Module)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .map(|(i, l)| (format!("layers.{}", i), l as &dyn Module))
+            .collect()
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+        for child in self.children() {
+            child.set_training(training);
+        }
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
 }
-fn print_params(params: &Vec<&Value>) {
-    for p in params.iter() {
-        print_value(p);
+
+/// Every layer in turn, unchecked, so an `MLP` can sit inside a
+/// [`Sequential`](crate::mlp::sequential::Sequential).
+impl Forward for MLP {
+    fn forward(&self, x: Vec<Value>) -> Vec<Value> {
+        self.layers
+            .iter()
+            .fold(x, |y, layer| layer.call_unchecked(&y))
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::mlp::init::{Constant, KaimingUniform, Orthogonal, XavierNormal, XavierUniform};
+    use crate::mlp::state_dict::StateDict;
+    use crate::ops::gradcheck::check_grad;
+    use rand::Rng;
+
+    fn print_value(value: &Value) {
+        println!(
+            "param: label:{:?}, data:{:?},grad:{:?}",
+            value.get_label(),
+            value.get_data(),
+            value.get_grad()
+        );
+    }
+    fn print_params(params: &Vec<&Value>) {
+        for p in params.iter() {
+            print_value(p);
+        }
+    }
 
     #[test]
     fn test_mlp() {
         // let x = [2.0, 3.0, -1.0];
-        let x = [2.0, 3.0, -1.0];
+        let x = [2.0, 3.0];
 
         // let mlp = MLP::new(3, &[4, 4, 1]);
         let mlp = MLP::new(2, &[4, 4, 1]);
-        let output = mlp.call(&x);
+        let output = mlp.call(&x).unwrap();
         output.backward();
         println!("output: {:?}", output);
         let params = mlp.parameters();
@@ -73,7 +335,7 @@ mod test {
     fn test_nn() {
         let x = [2.0, 3.0, -1.0];
         let mlp = MLP::new(3, &[4, 4, 1]);
-        let output = mlp.call(&x);
+        let output = mlp.call(&x).unwrap();
         println!("MLP output: {:?}", output);
         let xs = [
             [2.0, 3.0, -1.0],
@@ -84,8 +346,8 @@ mod test {
         let ys = [1.0, -1.0, -1.0, 1.0];
         let mut loss_v = vec![];
         let mut ypred: Vec<Value> = vec![];
-        for i in 0..200 {
-            ypred = xs.iter().map(|x| mlp.call(x)).collect();
+        for _ in 0..200 {
+            ypred = xs.iter().map(|x| mlp.call(x).unwrap()).collect();
 
             // loss = sum( (yout - ygt)**2 for ygt,yout in zip(ys,ypred))
             let loss: Value = ys
@@ -115,4 +377,640 @@ mod test {
             .iter()
             .for_each(|yp| println!("Predicted :{}", yp.get_data()));
     }
+
+    const ACTIVATIONS: [Activation; 13] = [
+        Activation::Identity,
+        Activation::Relu,
+        Activation::LeakyRelu(0.1),
+        Activation::Elu(1.0),
+        Activation::Selu,
+        Activation::Tanh,
+        Activation::HardTanh,
+        Activation::Sigmoid,
+        Activation::Gelu,
+        Activation::GeluTanh,
+        Activation::Silu,
+        Activation::Mish,
+        Activation::Softplus,
+    ];
+
+    #[test]
+    fn test_builder() {
+        let mlp = MLP::builder(2)
+            .dense(16, Activation::Tanh)
+            .dense(16, Activation::Gelu)
+            .dense(1, Activation::Identity)
+            .build();
+        let cal_params = ((2 * 16) + 16) + ((16 * 16) + 16) + (16 + 1);
+        assert_eq!(mlp.parameters().len(), cal_params);
+        assert!(mlp.call(&[0.5, -0.5]).unwrap().get_data().is_finite());
+    }
+
+    #[test]
+    fn test_default_activation() {
+        // A hidden layer of one neuron makes the output b2 + w2 * act(h).
+        let mlp = MLP::new(1, &[1, 1]);
+        let p = mlp.parameters();
+        let (w1, b1, w2, b2) = (p[0], p[1], p[2], p[3]);
+        w1.set_data(1.0);
+        w2.set_data(2.0);
+        b2.set_data(0.5);
+        b1.set_data(-3.0);
+        assert_eq!(mlp.call(&[1.0]).unwrap().get_data(), 0.5);
+        b1.set_data(3.0);
+        assert_eq!(mlp.call(&[1.0]).unwrap().get_data(), 8.5);
+    }
+
+    #[test]
+    fn test_activations() {
+        // Every activation, as the hidden activation of a full model: the
+        // one-neuron model matches act applied by hand, and backward matches
+        // central differences on every parameter.
+        let x = [0.3, -0.7, 1.1];
+        for act in ACTIVATIONS {
+            let mlp = MLP::builder(1)
+                .dense(1, act)
+                .dense(1, Activation::Identity)
+                .build();
+            let p = mlp.parameters();
+            p[0].set_data(0.8);
+            p[1].set_data(-0.2);
+            p[2].set_data(1.5);
+            let h = act.apply(Value::newd(0.8 * 0.4 - 0.2, "h".to_string()));
+            assert!((mlp.call(&[0.4]).unwrap().get_data() - 1.5 * h.get_data()).abs() < 1e-12);
+
+            let mlp = MLP::builder(3)
+                .dense(4, act)
+                .dense(3, act)
+                .dense(1, Activation::Identity)
+                .seed(11)
+                .build();
+            check_grad(|| mlp.forward(&x).unwrap(), &mlp.parameters());
+        }
+    }
+
+    #[test]
+    fn test_train_activations() {
+        // Fits the four points of test_nn with each smooth hidden activation.
+        let xs = [
+            [2.0, 3.0, -1.0],
+            [3.0, -1.0, 0.5],
+            [0.5, 1.0, 1.0],
+            [1.0, 1.0, -1.0],
+        ];
+        let ys = [1.0, -1.0, -1.0, 1.0];
+        for act in [Activation::Tanh, Activation::Gelu, Activation::Silu] {
+            let mlp = MLP::builder(3)
+                .dense(8, act)
+                .dense(8, act)
+                .dense(1, Activation::Tanh)
+                .init(Uniform(-0.5, 0.5))
+                .seed(3)
+                .build();
+            let mut loss_v = vec![];
+            for _ in 0..300 {
+                let loss: Value = ys
+                    .iter()
+                    .zip(&xs)
+                    .map(|(ygt, x)| (mlp.call(x).unwrap() - *ygt).powf(2.0))
+                    .sum();
+                mlp.zero_grad();
+                loss.backward();
+                mlp.parameters().iter().for_each(|p| {
+                    p.set_data(p.get_data() - 0.05 * p.get_grad());
+                });
+                loss_v.push(loss.get_data());
+            }
+            assert!(
+                loss_v[loss_v.len() - 1] < 0.05,
+                "{:?}: loss {:?}",
+                act,
+                loss_v.last()
+            );
+        }
+    }
+
+    fn bits(mlp: &MLP) -> Vec<u64> {
+        mlp.parameters()
+            .iter()
+            .map(|p| p.get_data().to_bits())
+            .collect()
+    }
+
+    #[test]
+    fn test_seed() {
+        let build = |seed| {
+            MLP::builder(3)
+                .seed(seed)
+                .init(XavierNormal)
+                .dense_with(8, Activation::Relu, KaimingUniform)
+                .dense_with(8, Activation::Tanh, Orthogonal(1.0))
+                .dense(1, Activation::Identity)
+                .build()
+        };
+        assert_eq!(bits(&build(42)), bits(&build(42)));
+        assert_ne!(bits(&build(42)), bits(&build(43)));
+        let x = [0.1, 0.2, 0.3];
+        assert_eq!(
+            build(42).call(&x).unwrap().get_data().to_bits(),
+            build(42).call(&x).unwrap().get_data().to_bits()
+        );
+
+        // An explicit rng continues its stream across models.
+        let mut rng = StdRng::seed_from_u64(7);
+        let a = MLP::builder(2)
+            .dense(2, Activation::Relu)
+            .build_with_rng(&mut rng);
+        let b = MLP::builder(2)
+            .dense(2, Activation::Relu)
+            .build_with_rng(&mut rng);
+        assert_ne!(bits(&a), bits(&b));
+        let mut rng = StdRng::seed_from_u64(7);
+        let c = MLP::builder(2)
+            .dense(2, Activation::Relu)
+            .build_with_rng(&mut rng);
+        assert_eq!(bits(&a), bits(&c));
+    }
+
+    #[test]
+    fn test_init() {
+        let mlp = MLP::builder(2)
+            .init(Constant(0.5))
+            .dense(3, Activation::Identity)
+            .dense_with(
+                1,
+                Activation::Identity,
+                |fan_in: usize, _: usize, rng: &mut dyn RngCore| {
+                    rng.gen_range(0.0..1.0) / fan_in as f64
+                },
+            )
+            .build();
+        let params: Vec<f64> = mlp.parameters().iter().map(|p| p.get_data()).collect();
+        assert_eq!(params[..9], [0.5; 9]);
+        assert!(params[9..12].iter().all(|w| (0.0..1.0 / 3.0).contains(w)));
+        assert_eq!(params[12], 0.0);
+    }
+
+    #[test]
+    fn test_forward() {
+        let mlp = MLP::builder(2)
+            .seed(0)
+            .dense(4, Activation::Relu)
+            .dense(3, Activation::Identity)
+            .build();
+        let out = mlp.forward(&[0.5, -1.0]).unwrap();
+        assert_eq!(out.len(), 3);
+
+        // Output k is the bias plus the k-th row of the last layer applied
+        // to the hidden layer.
+        let p: Vec<f64> = mlp.parameters().iter().map(|p| p.get_data()).collect();
+        let hidden: Vec<f64> = (0..4)
+            .map(|j| (p[j * 3] * 0.5 - p[j * 3 + 1] + p[j * 3 + 2]).max(0.0))
+            .collect();
+        for (k, o) in out.iter().enumerate() {
+            let row = &p[12 + k * 5..12 + k * 5 + 5];
+            let expected = (0..4).map(|j| row[j] * hidden[j]).sum::<f64>() + row[4];
+            assert!((o.get_data() - expected).abs() < 1e-12);
+        }
+
+        let single = MLP::new(2, &[4, 1]);
+        assert_eq!(single.forward(&[0.5, -1.0]).unwrap().len(), 1);
+        assert_eq!(
+            single.forward(&[0.5, -1.0]).unwrap()[0].get_data(),
+            single.call(&[0.5, -1.0]).unwrap().get_data()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "use MLP::forward")]
+    fn test_call_multi_output() {
+        MLP::new(2, &[16, 3]).call(&[1.0, 2.0]).unwrap();
+    }
+
+    #[test]
+    fn test_multi_class() {
+        // Three classes by quadrant-ish region, cross entropy on all logits.
+        let xs = [
+            [1.0, 1.0],
+            [0.8, 1.2],
+            [-1.0, 1.0],
+            [-1.2, 0.7],
+            [0.0, -1.0],
+            [0.3, -1.2],
+        ];
+        let ys = [0, 0, 1, 1, 2, 2];
+        let mlp = MLP::builder(2)
+            .init(XavierUniform)
+            .seed(1)
+            .dense(8, Activation::Tanh)
+            .dense(3, Activation::Identity)
+            .build();
+        for _ in 0..100 {
+            let loss: Value = xs
+                .iter()
+                .zip(&ys)
+                .map(|(x, &y)| -Value::log_softmax(&mlp.forward(x).unwrap())[y].clone())
+                .sum();
+            mlp.zero_grad();
+            loss.backward();
+            mlp.parameters().iter().for_each(|p| {
+                p.set_data(p.get_data() - 0.1 * p.get_grad());
+            });
+        }
+        for (x, &y) in xs.iter().zip(&ys) {
+            assert_eq!(Value::argmax(&mlp.forward(x).unwrap()), y);
+        }
+    }
+
+    #[test]
+    fn test_checked() {
+        let mlp = MLP::new(2, &[4, 3]);
+        assert_eq!(
+            mlp.forward(&[2.0, 3.0, -1.0]).unwrap_err(),
+            NnError::ShapeMismatch {
+                expected: 2,
+                got: 3
+            }
+        );
+        assert_eq!(mlp.forward(&[]).unwrap_err(), NnError::EmptyInput);
+        assert_eq!(
+            mlp.forward(&[f64::NAN, 1.0]).unwrap_err().to_string(),
+            "non-finite input NaN at index 0"
+        );
+
+        let mlp = MLP::new(2, &[4, 1]);
+        assert!(mlp.call(&[1.0]).is_err());
+        assert_eq!(
+            mlp.call(&[1.0, 2.0]).unwrap().get_data(),
+            mlp.call_unchecked(&[1.0, 2.0]).get_data()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unchecked_mismatch() {
+        MLP::new(2, &[4, 1]).call_unchecked(&[1.0, 2.0, 3.0]);
+    }
+
+    fn moons() -> Vec<Vec<f64>> {
+        (0..32)
+            .map(|i| {
+                let t = i as f64 * 0.1;
+                vec![t.cos() - 0.5, t.sin() * 0.8]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_forward_batch() {
+        let mlp = MLP::builder(2)
+            .seed(5)
+            .dense(16, Activation::Relu)
+            .dense(16, Activation::Tanh)
+            .dense(2, Activation::Identity)
+            .build();
+        let xs = moons();
+        let batch = mlp.forward_batch(&xs).unwrap();
+        assert_eq!(batch.len(), 32);
+        let bits =
+            |ys: &[Value]| -> Vec<u64> { ys.iter().map(|y| y.get_data().to_bits()).collect() };
+        for (x, ys) in xs.iter().zip(&batch) {
+            assert_eq!(bits(ys), bits(&mlp.forward(x).unwrap()));
+        }
+
+        // One backward through the batch loss equals the summed per-sample
+        // gradients.
+        let loss: Value = batch.iter().map(|ys| ys[0].clone() + ys[1].clone()).sum();
+        mlp.zero_grad();
+        loss.backward();
+        let batched: Vec<f64> = mlp.parameters().iter().map(|p| p.get_grad()).collect();
+        mlp.zero_grad();
+        let mut summed = vec![0.0; batched.len()];
+        for x in xs.iter() {
+            let ys = mlp.forward(x).unwrap();
+            (ys[0].clone() + ys[1].clone()).backward();
+            for (s, p) in summed.iter_mut().zip(mlp.parameters()) {
+                *s += p.get_grad();
+            }
+            mlp.zero_grad();
+        }
+        for (a, b) in batched.iter().zip(&summed) {
+            assert!((a - b).abs() < 1e-9 * (1.0 + b.abs()));
+        }
+    }
+
+    #[test]
+    fn test_forward_batch_checked() {
+        let mlp = MLP::new(2, &[4, 1]);
+        let xs = vec![vec![1.0, 2.0], vec![1.0], vec![f64::NAN, 0.0]];
+        assert_eq!(
+            mlp.forward_batch(&xs).unwrap_err(),
+            NnError::ShapeMismatch {
+                expected: 2,
+                got: 1
+            }
+        );
+        assert!(mlp.forward_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_forward_batch_dropout() {
+        // Any activation and dropout in training mode: the batch draws the
+        // masks the per-sample passes would, bit for bit, and the tensor
+        // path draws them too, in sample order.
+        let model = || {
+            MLP::builder(2)
+                .seed(3)
+                .dense(8, Activation::Gelu)
+                .dropout(0.5)
+                .dense(2, Activation::Sigmoid)
+                .build()
+        };
+        let (batched, single, tensor) = (model(), model(), model());
+        let xs = moons();
+        let batch = batched.forward_batch(&xs).unwrap();
+        let bits =
+            |ys: &[Value]| -> Vec<u64> { ys.iter().map(|y| y.get_data().to_bits()).collect() };
+        for (x, ys) in xs.iter().zip(&batch) {
+            assert_eq!(bits(ys), bits(&single.forward(x).unwrap()));
+        }
+        let x = Tensor::newd(xs.concat(), &[32, 2], "x".to_string());
+        let y = tensor.forward_tensor(&x).unwrap().get_data();
+        for (a, b) in y.iter().zip(batch.iter().flatten()) {
+            assert!(
+                (a - b.get_data()).abs() < 1e-12,
+                "{} vs {}",
+                a,
+                b.get_data()
+            );
+        }
+    }
+
+    #[test]
+    fn test_forward_tensor() {
+        let mlp = MLP::builder(2)
+            .seed(5)
+            .dense(16, Activation::Relu)
+            .dense(16, Activation::Tanh)
+            .dense(2, Activation::Identity)
+            .build();
+        let xs = moons();
+        let x = Tensor::newd(xs.concat(), &[32, 2], "x".to_string());
+        let y = mlp.forward_tensor(&x).unwrap();
+        assert_eq!(y.shape(), &[32, 2]);
+        let expected: Vec<f64> = mlp
+            .forward_batch(&xs)
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(|v| v.get_data())
+            .collect();
+        for (a, b) in y.get_data().iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-12, "{} vs {}", a, b);
+        }
+
+        // Training through the tensor path moves the model's own weights.
+        let before = bits(&mlp);
+        y.sum().backward();
+        assert!(mlp.parameters().iter().any(|p| p.get_grad() != 0.0));
+        for p in mlp.parameters() {
+            p.set_data(p.get_data() - 0.01 * p.get_grad());
+        }
+        assert_ne!(bits(&mlp), before);
+
+        assert_eq!(
+            mlp.forward_tensor(&Tensor::zeros(&[4, 3])).unwrap_err(),
+            NnError::ShapeMismatch {
+                expected: 2,
+                got: 3
+            }
+        );
+        assert_eq!(
+            mlp.forward_tensor(&Tensor::zeros(&[0, 2])).unwrap_err(),
+            NnError::EmptyInput
+        );
+        let bad = Tensor::newd(vec![0.0, f64::INFINITY], &[1, 2], "x".to_string());
+        assert!(matches!(
+            mlp.forward_tensor(&bad),
+            Err(NnError::NonFiniteInput { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_named_parameters() {
+        let mlp = MLP::new(3, &[8, 2]);
+        let named = mlp.named_parameters();
+        assert_eq!(named.len(), mlp.num_parameters());
+        assert_eq!(mlp.num_parameters(), 3 * 8 + 8 + 8 * 2 + 2);
+        assert_eq!(named[0].0, "layers.0.neurons.0.w0");
+        assert_eq!(named[3].0, "layers.0.neurons.0.b");
+        assert_eq!(named[4].0, "layers.0.neurons.1.w0");
+        assert_eq!(named.last().unwrap().0, "layers.1.neurons.1.b");
+        let paths: Vec<&str> = named.iter().map(|(n, _)| n.as_str()).collect();
+        assert!(paths.contains(&"layers.1.neurons.1.w7"));
+
+        // Same order and the very same leaves as parameters().
+        for ((_, a), b) in named.iter().zip(mlp.parameters()) {
+            assert!(std::ptr::eq(*a, b));
+        }
+    }
+
+    #[test]
+    fn test_named_modules() {
+        let mlp = MLP::new(3, &[4, 1]);
+        assert_eq!(mlp.children().len(), 2);
+        let modules = mlp.named_modules();
+        let paths: Vec<&str> = modules.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(paths.len(), 2 + 4 + 1);
+        assert_eq!(
+            paths[..3],
+            ["layers.0", "layers.0.neurons.0", "layers.0.neurons.1"]
+        );
+        assert_eq!(paths[5..], ["layers.1", "layers.1.neurons.0"]);
+        assert_eq!(modules[0].1.num_parameters(), 4 * 4);
+        assert_eq!(modules[1].1.num_parameters(), 4);
+        assert!(modules[1].1.children().is_empty());
+    }
+
+    #[test]
+    fn test_freeze() {
+        let mlp = MLP::builder(2)
+            .seed(0)
+            .dense(4, Activation::Relu)
+            .dense(1, Activation::Identity)
+            .build();
+        let trainable = mlp.trainable_parameters(&["layers.0", "layers.1.neurons.0.b"]);
+        assert_eq!(trainable.len(), 4);
+        let before: Vec<(String, f64)> = mlp
+            .named_parameters()
+            .iter()
+            .map(|(n, p)| (n.clone(), p.get_data()))
+            .collect();
+
+        let out = mlp.call(&[1.0, -1.0]).unwrap();
+        mlp.zero_grad();
+        out.backward();
+        for p in trainable {
+            p.set_data(p.get_data() - 0.1 * p.get_grad());
+        }
+        for ((name, p), (_, old)) in mlp.named_parameters().iter().zip(&before) {
+            let frozen = name.starts_with("layers.0.") || name == "layers.1.neurons.0.b";
+            assert_eq!(
+                p.get_data() == *old,
+                frozen || p.get_grad() == 0.0,
+                "{}",
+                name
+            );
+        }
+
+        // A prefix only matches whole path components.
+        assert_eq!(
+            mlp.trainable_parameters(&["layers.0.neurons.1"]).len(),
+            17 - 3
+        );
+        assert_eq!(
+            mlp.trainable_parameters(&["layers.0.neurons.1.w"]).len(),
+            17
+        );
+        assert_eq!(mlp.trainable_parameters(&[]).len(), 17);
+    }
+
+    #[test]
+    fn test_state_dict() {
+        let build = |seed| {
+            MLP::builder(2)
+                .seed(seed)
+                .dense(4, Activation::Tanh)
+                .dense(2, Activation::Identity)
+                .build()
+        };
+        let (a, b) = (build(1), build(2));
+        let state = a.state_dict();
+        assert_eq!(state.len(), a.num_parameters());
+        assert_eq!(
+            state.get("layers.1.neurons.1.w3"),
+            Some(a.parameters()[20].get_data())
+        );
+
+        // Through both file formats, b becomes a bit for bit.
+        let x = [0.3, -0.8];
+        for state in [
+            StateDict::from_bytes(&state.to_bytes()).unwrap(),
+            StateDict::from_json(&state.to_json().unwrap()).unwrap(),
+        ] {
+            b.load_state_dict(&state).unwrap();
+            assert_eq!(bits(&a), bits(&b));
+            assert_eq!(
+                a.forward(&x).unwrap()[1].get_data().to_bits(),
+                b.forward(&x).unwrap()[1].get_data().to_bits()
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_state_dict_mismatch() {
+        let mlp = MLP::new(2, &[3, 1]);
+        let before = bits(&mlp);
+
+        // A wider first layer: extra inputs and neurons are unexpected.
+        let wider = MLP::new(3, &[4, 1]).state_dict();
+        match mlp.load_state_dict(&wider).unwrap_err() {
+            NnError::StateDictMismatch {
+                missing,
+                unexpected,
+            } => {
+                assert!(missing.is_empty());
+                assert!(unexpected.contains(&"layers.0.neurons.0.w2".to_string()));
+                assert!(unexpected.contains(&"layers.0.neurons.3.b".to_string()));
+                // layers.1 gains an input.
+                assert!(unexpected.contains(&"layers.1.neurons.0.w3".to_string()));
+                assert_eq!(unexpected.len(), 4 + 3 + 1);
+            }
+            err => panic!("{:?}", err),
+        }
+
+        // A deeper model saved: one layer missing here, one unexpected.
+        let mut state = mlp.state_dict();
+        state.insert("layers.2.neurons.0.b".to_string(), 0.0);
+        let mut partial = StateDict::new();
+        for (name, v) in state.iter().filter(|(n, _)| *n != "layers.1.neurons.0.w1") {
+            partial.insert(name.to_string(), v);
+        }
+        let err = mlp.load_state_dict(&partial).unwrap_err();
+        assert_eq!(
+            err,
+            NnError::StateDictMismatch {
+                missing: vec!["layers.1.neurons.0.w1".to_string()],
+                unexpected: vec!["layers.2.neurons.0.b".to_string()],
+            }
+        );
+        assert!(err
+            .to_string()
+            .contains("missing keys [layers.1.neurons.0.w1]"));
+
+        // Failed loads change nothing.
+        assert_eq!(bits(&mlp), before);
+    }
+
+    #[test]
+    fn test_eval_deterministic() {
+        let model = || {
+            MLP::builder(2)
+                .seed(8)
+                .dense(32, Activation::Tanh)
+                .dropout(0.5)
+                .dense(32, Activation::Relu)
+                .dropout(0.2)
+                .dense(1, Activation::Identity)
+                .build()
+        };
+        let out = |mlp: &MLP| mlp.call(&[0.4, -0.7]).unwrap().get_data();
+        let mlp = model();
+        assert!(mlp.is_training());
+
+        // Training draws fresh masks every call, seeded by the builder.
+        let train: Vec<f64> = (0..4).map(|_| out(&mlp)).collect();
+        assert_ne!(train[0], train[1]);
+        let again = model();
+        assert_eq!((0..4).map(|_| out(&again)).collect::<Vec<_>>(), train);
+
+        // Dropout does not change the weights drawn from the seed.
+        let plain = MLP::builder(2)
+            .seed(8)
+            .dense(32, Activation::Tanh)
+            .dense(32, Activation::Relu)
+            .dense(1, Activation::Identity)
+            .build();
+        assert_eq!(bits(&mlp), bits(&plain));
+        assert_eq!(
+            mlp.state_dict().keys().collect::<Vec<_>>(),
+            plain.state_dict().keys().collect::<Vec<_>>()
+        );
+
+        mlp.eval();
+        assert!(!mlp.is_training());
+        // Neurons behave the same in both modes and do not track it.
+        let tracked = |mlp: &MLP| -> Vec<bool> {
+            mlp.named_modules()
+                .into_iter()
+                .filter(|(path, _)| !path.contains("neurons"))
+                .map(|(_, m)| m.is_training())
+                .collect()
+        };
+        assert_eq!(tracked(&mlp), [false; 5]);
+        let eval: Vec<f64> = (0..4).map(|_| out(&mlp)).collect();
+        assert!(eval.iter().all(|&y| y.to_bits() == out(&plain).to_bits()));
+        let x = Tensor::newd(vec![0.4, -0.7], &[1, 2], "x".to_string());
+        assert!((mlp.forward_tensor(&x).unwrap().get_data()[0] - eval[0]).abs() < 1e-12);
+
+        mlp.train();
+        assert_eq!(tracked(&mlp), [true; 5]);
+        assert_ne!(out(&mlp), out(&mlp));
+    }
+
+    #[test]
+    #[should_panic(expected = "no layer")]
+    fn test_dropout_first() {
+        MLP::builder(2).dropout(0.5);
+    }
 }
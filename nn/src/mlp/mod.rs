@@ -1,4 +1,9 @@
-mod layer;
+pub mod dropout;
+pub mod init;
+pub mod layer;
+#[allow(clippy::module_inception)]
 pub mod mlp;
 pub mod module;
-mod neuron;
+pub mod neuron;
+pub mod sequential;
+pub mod state_dict;
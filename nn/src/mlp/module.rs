@@ -1,4 +1,7 @@
+use crate::error::NnError;
+use crate::mlp::state_dict::StateDict;
 use crate::tensor::value::Value;
+use std::collections::HashSet;
 pub trait Module {
     fn zero_grad(&self) {
         for p in self.parameters() {
@@ -8,4 +11,157 @@ pub trait Module {
     fn parameters(&self) -> Vec<&Value> {
         vec![]
     }
+
+    /// Direct submodules, each with its name within this module.
+    fn named_children(&self) -> Vec<(String, &dyn Module)> {
+        vec![]
+    }
+
+    fn children(&self) -> Vec<&dyn Module> {
+        self.named_children().into_iter().map(|(_, m)| m).collect()
+    }
+
+    /// Every submodule, depth first, with its dotted path such as
+    /// `layers.1.neurons.7`. The module itself is not included.
+    fn named_modules(&self) -> Vec<(String, &dyn Module)> {
+        self.named_children()
+            .into_iter()
+            .flat_map(|(name, child)| {
+                let nested = child
+                    .named_modules()
+                    .into_iter()
+                    .map(|(path, m)| (format!("{}.{}", name, path), m))
+                    .collect::<Vec<_>>();
+                std::iter::once((name, child)).chain(nested)
+            })
+            .collect()
+    }
+
+    /// Every parameter with its dotted path such as `layers.1.neurons.7.w3`,
+    /// in the order of [`parameters`](Module::parameters).
+    ///
+    /// By default a module with children names its parameters after theirs,
+    /// and a module without numbers them. Modules holding parameters of their
+    /// own next to children must override this.
+    fn named_parameters(&self) -> Vec<(String, &Value)> {
+        let children = self.named_children();
+        if children.is_empty() {
+            return self
+                .parameters()
+                .into_iter()
+                .enumerate()
+                .map(|(i, p)| (i.to_string(), p))
+                .collect();
+        }
+        children
+            .into_iter()
+            .flat_map(|(name, child)| {
+                child
+                    .named_parameters()
+                    .into_iter()
+                    .map(move |(path, p)| (format!("{}.{}", name, path), p))
+            })
+            .collect()
+    }
+
+    /// Number of scalar parameters.
+    fn num_parameters(&self) -> usize {
+        self.parameters().len()
+    }
+
+    /// Parameters outside the `frozen` paths, for an update step that leaves
+    /// those untouched. A path freezes itself and everything below it:
+    /// `layers.0` covers `layers.0.neurons.3.w1` but not `layers.01`.
+    fn trainable_parameters(&self, frozen: &[&str]) -> Vec<&Value> {
+        self.named_trainable_parameters(frozen)
+            .into_iter()
+            .map(|(_, p)| p)
+            .collect()
+    }
+
+    /// [`trainable_parameters`](Module::trainable_parameters) with their
+    /// paths, as an [`Optimizer`](crate::train::optim::Optimizer) takes them.
+    fn named_trainable_parameters(&self, frozen: &[&str]) -> Vec<(String, &Value)> {
+        let is_frozen = |path: &str| {
+            frozen.iter().any(|f| {
+                path.strip_prefix(f)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+        };
+        self.named_parameters()
+            .into_iter()
+            .filter(|(path, _)| !is_frozen(path))
+            .collect()
+    }
+
+    /// Puts this module and every submodule in training mode, the mode new
+    /// modules start in.
+    fn train(&self) {
+        self.set_training(true);
+    }
+
+    /// Puts this module and every submodule in evaluation mode, where
+    /// modules such as [`Dropout`](crate::mlp::dropout::Dropout) are
+    /// deterministic.
+    fn eval(&self) {
+        self.set_training(false);
+    }
+
+    /// Sets the mode of this module and every submodule. Modules that track
+    /// their mode override this to record it and then recurse; the default
+    /// only recurses.
+    fn set_training(&self, training: bool) {
+        for child in self.children() {
+            child.set_training(training);
+        }
+    }
+
+    /// Whether the module is in training mode. Modules that behave the same
+    /// in both modes need not track it and report `true`.
+    fn is_training(&self) -> bool {
+        true
+    }
+
+    /// Current parameter values keyed by their paths.
+    fn state_dict(&self) -> StateDict {
+        let mut state = StateDict::new();
+        for (name, p) in self.named_parameters() {
+            state.insert(name, p.get_data());
+        }
+        state
+    }
+
+    /// Sets every parameter from `state`. Its keys must be exactly this
+    /// module's parameter paths; otherwise nothing is changed and the
+    /// missing and unexpected keys are returned.
+    fn load_state_dict(&self, state: &StateDict) -> Result<(), NnError> {
+        let named = self.named_parameters();
+        let names: HashSet<&str> = named.iter().map(|(n, _)| n.as_str()).collect();
+        let missing: Vec<String> = named
+            .iter()
+            .filter(|(n, _)| state.get(n).is_none())
+            .map(|(n, _)| n.clone())
+            .collect();
+        let unexpected: Vec<String> = state
+            .keys()
+            .filter(|k| !names.contains(k))
+            .map(str::to_string)
+            .collect();
+        if !missing.is_empty() || !unexpected.is_empty() {
+            return Err(NnError::StateDictMismatch {
+                missing,
+                unexpected,
+            });
+        }
+        for (name, p) in named {
+            p.set_data(state.get(&name).unwrap());
+        }
+        Ok(())
+    }
+}
+
+/// A module mapping a vector of values to another, the building block of a
+/// [`Sequential`](crate::mlp::sequential::Sequential).
+pub trait Forward: Module {
+    fn forward(&self, x: Vec<Value>) -> Vec<Value>;
 }
@@ -1,54 +1,50 @@
+use crate::error::{check_input, NnError};
 use crate::mlp::module::Module;
+use crate::ops::activation::Activation;
 use crate::tensor::value::Value;
-use rand::distributions::{Distribution, Uniform};
-use std::{borrow::Borrow, rc::Rc};
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct Neuron {
     w: Rc<Vec<Value>>,
     b: Value,
-    nonlin: bool,
+    act: Activation,
 }
 
 impl Neuron {
-    pub fn new(nin: usize, nonlin: bool, l: f64) -> Neuron {
-        let mut rng = rand::thread_rng();
-        let die = Uniform::from(-1.0..1.0);
+    pub fn new(w: &[f64], b: f64, act: Activation) -> Neuron {
         let w = Rc::new(
-            (0..nin)
-                .map(|i| {
-                    Value::new(
-                        die.sample(&mut rng),
-                        // 0.0001 + rng.gen_range(-1.0..1.0),
-                        // (l * 0.001) + 0.1 * i as f64 * l as f64,
-                        // 2.0 * i as f64 * l as f64,
-                        vec![],
-                        "".to_string(),
-                        format!("w{}", i),
-                    )
-                })
+            w.iter()
+                .enumerate()
+                .map(|(i, &w)| Value::newd(w, format!("w{}", i)))
                 .collect(),
         );
         Neuron {
             w,
-            b: Value::newd(0.0, "b".to_string()),
-            nonlin,
+            b: Value::newd(b, "b".to_string()),
+            act,
         }
     }
 
-    pub fn call(&self, x: Vec<Value>) -> Value {
-        let act = self
-            .w
-            .iter()
-            .zip(x.iter())
-            .map(|(wi, xi)| wi.clone() * xi.clone())
-            .sum::<Value>()
-            + self.b.clone();
-        if self.nonlin {
-            act.relu()
-        } else {
-            act
-        }
+    pub(crate) fn weights(&self) -> &[Value] {
+        &self.w
+    }
+
+    pub(crate) fn bias(&self) -> &Value {
+        &self.b
+    }
+
+    /// `act(w . x + b)`, after checking `x` against the neuron's inputs.
+    pub fn call(&self, x: &[Value]) -> Result<Value, NnError> {
+        check_input(self.w.len(), x.iter().map(Value::get_data))?;
+        Ok(self.call_unchecked(x))
+    }
+
+    /// [`call`](Neuron::call) without the checks. Panics on a length
+    /// mismatch.
+    pub fn call_unchecked(&self, x: &[Value]) -> Value {
+        let act = Value::dot(&self.w, x) + self.b.clone();
+        self.act.apply(act)
     }
 }
 
@@ -58,6 +54,17 @@ impl Module for Neuron {
         params.push(&self.b);
         params
     }
+
+    fn named_parameters(&self) -> Vec<(String, &Value)> {
+        let mut params: Vec<(String, &Value)> = self
+            .w
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (format!("w{}", i), w))
+            .collect();
+        params.push(("b".to_string(), &self.b));
+        params
+    }
 }
 
 #[cfg(test)]
@@ -67,9 +74,67 @@ mod test {
 
     #[test]
     fn test_neuron() {
-        let neuron = Neuron::new(3, true, 0.1);
-        let output = neuron.call(Value::vec(&[1.0, 2.0, 3.0]));
+        let neuron = Neuron::new(&[0.5, -0.25, 1.0], 0.0, Activation::Relu);
+        let output = neuron.call(&Value::vec(&[1.0, 2.0, 3.0])).unwrap();
         output.backward();
         println!("meuron output: {:#?}", output);
     }
+
+    #[test]
+    fn test_neuron_activation() {
+        let neuron = Neuron::new(&[0.3, 0.8], 0.0, Activation::Sigmoid);
+        let x = Value::vec(&[1.0, -1.0]);
+        let pre = neuron.w[0].get_data() - neuron.w[1].get_data();
+        let output = neuron.call(&x).unwrap();
+        assert!((output.get_data() - 1.0 / (1.0 + f64::exp(-pre))).abs() < 1e-12);
+
+        output.backward();
+        let s = output.get_data();
+        assert_eq!(neuron.b.get_grad(), s * (1.0 - s));
+    }
+
+    #[test]
+    fn test_neuron_graph_depth() {
+        let neuron = Neuron::new(&[0.1; 16], 0.0, Activation::Identity);
+        let x = Value::vec(&[0.5; 16]);
+        let output = neuron.call(&x).unwrap();
+        // out = dot(w, x) + b: one add node over a single dot node.
+        assert_eq!(output._prev.len(), 2);
+        assert_eq!(output._prev[0]._prev.len(), 32);
+        output.backward();
+        for (wi, xi) in neuron.w.iter().zip(x.iter()) {
+            assert_eq!(wi.get_grad(), 0.5);
+            assert_eq!(xi.get_grad(), wi.get_data());
+        }
+        assert_eq!(neuron.b.get_grad(), 1.0);
+    }
+
+    #[test]
+    fn test_neuron_checked() {
+        let neuron = Neuron::new(&[0.5, -0.25, 1.0], 0.0, Activation::Relu);
+        assert_eq!(
+            neuron.call(&Value::vec(&[1.0, 2.0])).unwrap_err(),
+            NnError::ShapeMismatch {
+                expected: 3,
+                got: 2
+            }
+        );
+        assert_eq!(neuron.call(&[]).unwrap_err(), NnError::EmptyInput);
+        assert!(matches!(
+            neuron.call(&Value::vec(&[1.0, f64::NAN, 0.0])),
+            Err(NnError::NonFiniteInput { index: 1, .. })
+        ));
+        let x = Value::vec(&[1.0, 2.0, 3.0]);
+        assert_eq!(
+            neuron.call(&x).unwrap().get_data(),
+            neuron.call_unchecked(&x).get_data()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_neuron_unchecked_mismatch() {
+        let neuron = Neuron::new(&[0.5, -0.25, 1.0], 0.0, Activation::Relu);
+        neuron.call_unchecked(&Value::vec(&[1.0, 2.0]));
+    }
 }
@@ -0,0 +1,290 @@
+use crate::mlp::module::{Forward, Module};
+use crate::ops::activation::Activation;
+use crate::tensor::value::Value;
+use std::cell::Cell;
+
+/// Modules applied one after the other, each taking the previous output.
+///
+/// ```
+/// use nn::mlp::dropout::Dropout;
+/// use nn::mlp::layer::Layer;
+/// use nn::mlp::module::Module;
+/// use nn::mlp::sequential::Sequential;
+/// use nn::ops::activation::Activation;
+///
+/// let id = Activation::Identity;
+/// let model = Sequential::new()
+///     .append(Layer::new(2, 16, id))
+///     .append(Activation::Tanh)
+///     .append(Dropout::new(0.1).seed(1))
+///     .append(Layer::new(16, 1, id));
+/// assert_eq!(model.call(&[0.5, -0.5]).len(), 1);
+/// assert_eq!(model.parameters().len(), 2 * 16 + 16 + 16 + 1);
+/// ```
+pub struct Sequential {
+    modules: Vec<Box<dyn Forward>>,
+    training: Cell<bool>,
+}
+
+impl Default for Sequential {
+    fn default() -> Sequential {
+        Sequential::new()
+    }
+}
+
+impl Sequential {
+    pub fn new() -> Sequential {
+        Sequential {
+            modules: vec![],
+            training: Cell::new(true),
+        }
+    }
+
+    /// Appends `module` after the current ones.
+    pub fn append(mut self, module: impl Forward + 'static) -> Sequential {
+        self.push(Box::new(module));
+        self
+    }
+
+    /// Appends `module`, switching it to this container's mode.
+    pub fn push(&mut self, module: Box<dyn Forward>) {
+        module.set_training(self.training.get());
+        self.modules.push(module);
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// The model on plain inputs. Panics where a dense layer gets the wrong
+    /// number of inputs.
+    pub fn call(&self, x: &[f64]) -> Vec<Value> {
+        self.forward(Value::vec(x))
+    }
+}
+
+impl Module for Sequential {
+    fn parameters(&self) -> Vec<&Value> {
+        self.modules.iter().flat_map(|m| m.parameters()).collect()
+    }
+
+    /// Named by position: `0`, `1`, ...
+    fn named_children(&self) -> Vec<(String, &dyn Module)> {
+        self.modules
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (i.to_string(), m.as_ref() as &dyn Module))
+            .collect()
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+        for child in self.children() {
+            child.set_training(training);
+        }
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+}
+
+impl Forward for Sequential {
+    fn forward(&self, x: Vec<Value>) -> Vec<Value> {
+        self.modules.iter().fold(x, |y, m| m.forward(y))
+    }
+}
+
+impl Module for Activation {}
+
+/// Elementwise.
+impl Forward for Activation {
+    fn forward(&self, x: Vec<Value>) -> Vec<Value> {
+        x.into_iter().map(|x| self.apply(x)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mlp::dropout::Dropout;
+    use crate::mlp::init::{Uniform, XavierNormal};
+    use crate::mlp::layer::Layer;
+    use crate::mlp::mlp::MLP;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// A custom module: one learned scale per input.
+    struct Scale {
+        s: Vec<Value>,
+    }
+
+    impl Module for Scale {
+        fn parameters(&self) -> Vec<&Value> {
+            self.s.iter().collect()
+        }
+    }
+
+    impl Forward for Scale {
+        fn forward(&self, x: Vec<Value>) -> Vec<Value> {
+            x.into_iter()
+                .zip(&self.s)
+                .map(|(x, s)| x * s.clone())
+                .collect()
+        }
+    }
+
+    fn data<'a>(ys: impl IntoIterator<Item = &'a Value>) -> Vec<f64> {
+        ys.into_iter().map(|y| y.get_data()).collect()
+    }
+
+    #[test]
+    fn test_matches_mlp() {
+        // The same layers drawn from the same stream as MLP::builder.
+        let mlp = MLP::builder(3)
+            .dense(4, Activation::Relu)
+            .dense(2, Activation::Identity)
+            .build_with_rng(&mut StdRng::seed_from_u64(4));
+        let mut rng = StdRng::seed_from_u64(4);
+        let init = Uniform(-1.0, 1.0);
+        let seq = Sequential::new()
+            .append(Layer::with_init(
+                3,
+                4,
+                Activation::Identity,
+                &init,
+                &mut rng,
+            ))
+            .append(Activation::Relu)
+            .append(Layer::with_init(
+                4,
+                2,
+                Activation::Identity,
+                &init,
+                &mut rng,
+            ));
+        let x = [0.5, -1.0, 2.0];
+        assert_eq!(data(&seq.call(&x)), data(&mlp.forward(&x).unwrap()));
+        assert_eq!(data(seq.parameters()), data(mlp.parameters()));
+        assert_eq!(seq.len(), 3);
+    }
+
+    #[test]
+    fn test_mixed() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let inner = Sequential::new().append(Scale {
+            s: Value::vec(&[2.0, 3.0]),
+        });
+        let seq = Sequential::new()
+            .append(Layer::with_init(
+                2,
+                2,
+                Activation::Tanh,
+                &XavierNormal,
+                &mut rng,
+            ))
+            .append(Dropout::new(0.0))
+            .append(inner)
+            .append(Activation::Sigmoid)
+            .append(
+                MLP::builder(2)
+                    .seed(1)
+                    .dense(1, Activation::Identity)
+                    .build(),
+            );
+        // 6 dense + 2 scales + 3 from the nested MLP.
+        let params = seq.parameters();
+        assert_eq!(params.len(), 11);
+        assert_eq!(data(params[6..8].iter().copied()), vec![2.0, 3.0]);
+
+        let y = seq.call(&[0.3, -0.6]);
+        assert_eq!(y.len(), 1);
+        seq.zero_grad();
+        y[0].backward();
+        assert!(params.iter().all(|p| p.get_grad() != 0.0));
+        seq.zero_grad();
+        assert!(params.iter().all(|p| p.get_grad() == 0.0));
+    }
+
+    #[test]
+    fn test_empty() {
+        let seq = Sequential::new();
+        assert!(seq.is_empty());
+        assert_eq!(data(&seq.call(&[1.0, 2.0])), vec![1.0, 2.0]);
+        assert!(seq.parameters().is_empty());
+    }
+
+    #[test]
+    fn test_named() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let init = Uniform(-1.0, 1.0);
+        let seq = Sequential::new()
+            .append(Layer::with_init(
+                2,
+                2,
+                Activation::Identity,
+                &init,
+                &mut rng,
+            ))
+            .append(Activation::Relu)
+            .append(Sequential::new().append(Scale {
+                s: Value::vec(&[2.0, 3.0]),
+            }));
+        let names: Vec<String> = seq.named_parameters().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(
+            names,
+            [
+                "0.neurons.0.w0",
+                "0.neurons.0.w1",
+                "0.neurons.0.b",
+                "0.neurons.1.w0",
+                "0.neurons.1.w1",
+                "0.neurons.1.b",
+                "2.0.0",
+                "2.0.1",
+            ]
+        );
+        let modules: Vec<String> = seq.named_modules().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(
+            modules,
+            ["0", "0.neurons.0", "0.neurons.1", "1", "2", "2.0"]
+        );
+        assert_eq!(seq.children().len(), 3);
+        assert_eq!(seq.num_parameters(), 8);
+    }
+
+    #[test]
+    fn test_train_eval() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let nested = Sequential::new().append(Dropout::new(0.5).seed(1));
+        let mut seq = Sequential::new()
+            .append(Layer::with_init(
+                2,
+                64,
+                Activation::Relu,
+                &XavierNormal,
+                &mut rng,
+            ))
+            .append(nested)
+            .append(Activation::Identity);
+        let x = [1.0, 0.5];
+        assert_ne!(data(&seq.call(&x)), data(&seq.call(&x)));
+
+        // eval reaches the dropout two containers down.
+        seq.eval();
+        assert!(!seq.is_training());
+        assert!(!seq.named_modules()[0].1.is_training());
+        assert_eq!(data(&seq.call(&x)), data(&seq.call(&x)));
+        // Modules pushed later take the container's mode.
+        seq.push(Box::new(Dropout::new(0.5).seed(2)));
+        assert_eq!(data(&seq.call(&x)), data(&seq.call(&x)));
+
+        seq.train();
+        assert!(seq.is_training());
+        assert_ne!(data(&seq.call(&x)), data(&seq.call(&x)));
+    }
+}
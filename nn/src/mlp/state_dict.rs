@@ -0,0 +1,524 @@
+//! Saved parameter values, keyed by
+//! [`named_parameters`](crate::mlp::module::Module::named_parameters) paths.
+//!
+//! # Binary format
+//!
+//! Everything is little-endian.
+//!
+//! | size         | field                                      |
+//! |--------------|--------------------------------------------|
+//! | 4            | magic, the bytes `NNSD`                    |
+//! | 4            | format version, `u32`, currently 1         |
+//! | 8            | number of entries `n`, `u64`               |
+//!
+//! followed by the table of `n` entries, in model order:
+//!
+//! | size         | field                                      |
+//! |--------------|--------------------------------------------|
+//! | 4            | name length in bytes `len`, `u32`          |
+//! | `len`        | name, UTF-8, e.g. `layers.1.neurons.7.w3`  |
+//! | 8            | value, `f64`                               |
+//!
+//! Nothing may follow the last entry.
+//!
+//! # JSON format
+//!
+//! The same table as a JSON object, values written so they read back
+//! bit-identical; non-finite values cannot be written:
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "parameters": {
+//!     "layers.0.neurons.0.w0": 0.25,
+//!     "layers.0.neurons.0.b": -1e-7
+//!   }
+//! }
+//! ```
+
+use crate::error::NnError;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"NNSD";
+pub const VERSION: u32 = 1;
+
+/// Named parameter values in model order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDict {
+    entries: Vec<(String, f64)>,
+    index: HashMap<String, usize>,
+}
+
+impl StateDict {
+    pub fn new() -> StateDict {
+        StateDict::default()
+    }
+
+    /// Sets `name`, appending it if new. Returns whether it was new.
+    pub fn insert(&mut self, name: String, value: f64) -> bool {
+        match self.index.get(&name) {
+            Some(&i) => {
+                self.entries[i].1 = value;
+                false
+            }
+            None => {
+                self.index.insert(name.clone(), self.entries.len());
+                self.entries.push((name, value));
+                true
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.index.get(name).map(|&i| self.entries[i].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.entries.iter().map(|(n, v)| (n.as_str(), *v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(n, _)| n.as_str())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u64).to_le_bytes());
+        for (name, value) in self.entries.iter() {
+            out.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<StateDict, NnError> {
+        let mut r = Reader::new(bytes, "state dict");
+        if r.take(4)? != MAGIC {
+            return Err(format_error("not a state dict, bad magic"));
+        }
+        let version = u32::from_le_bytes(r.array()?);
+        if version != VERSION {
+            return Err(NnError::UnsupportedVersion(version));
+        }
+        let n = u64::from_le_bytes(r.array()?);
+        let mut state = StateDict::new();
+        for _ in 0..n {
+            let len = u32::from_le_bytes(r.array()?) as usize;
+            let name = std::str::from_utf8(r.take(len)?)
+                .map_err(|_| format_error("parameter name is not UTF-8"))?
+                .to_string();
+            let value = f64::from_le_bytes(r.array()?);
+            if !state.insert(name.clone(), value) {
+                return Err(format_error(&format!("duplicate parameter {}", name)));
+            }
+        }
+        r.finish()?;
+        Ok(state)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), NnError> {
+        fs::write(path, self.to_bytes()).map_err(io_error)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<StateDict, NnError> {
+        StateDict::from_bytes(&fs::read(path).map_err(io_error)?)
+    }
+
+    /// Fails on non-finite values, which JSON cannot hold.
+    pub fn to_json(&self) -> Result<String, NnError> {
+        let mut out = format!("{{\n  \"version\": {},\n  \"parameters\": {{", VERSION);
+        for (i, (name, value)) in self.entries.iter().enumerate() {
+            if !value.is_finite() {
+                return Err(format_error(&format!("{} is {}", name, value)));
+            }
+            let sep = if i == 0 { "" } else { "," };
+            // `{:?}` is the shortest representation that parses back exactly.
+            out.push_str(&format!("{}\n    {}: {:?}", sep, json_string(name), value));
+        }
+        out.push_str(if self.entries.is_empty() {
+            "}\n}\n"
+        } else {
+            "\n  }\n}\n"
+        });
+        Ok(out)
+    }
+
+    pub fn from_json(json: &str) -> Result<StateDict, NnError> {
+        let mut p = Parser { s: json, pos: 0 };
+        let mut version = None;
+        let mut state = None;
+        p.object(|p, key| {
+            match key.as_str() {
+                "version" => version = Some(p.number()?),
+                "parameters" => {
+                    let mut params = StateDict::new();
+                    p.object(|p, name| {
+                        let value = p.number()?;
+                        if !params.insert(name.clone(), value) {
+                            return Err(p.error(&format!("duplicate parameter {}", name)));
+                        }
+                        Ok(())
+                    })?;
+                    state = Some(params);
+                }
+                _ => return Err(p.error(&format!("unexpected field {}", key))),
+            }
+            Ok(())
+        })?;
+        p.skip_ws();
+        if p.pos != p.s.len() {
+            return Err(p.error("trailing characters"));
+        }
+        match version {
+            Some(v) if v == VERSION as f64 => {}
+            Some(v) => return Err(NnError::UnsupportedVersion(v as u32)),
+            None => return Err(format_error("missing version")),
+        }
+        state.ok_or_else(|| format_error("missing parameters"))
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), NnError> {
+        fs::write(path, self.to_json()?).map_err(io_error)
+    }
+
+    pub fn load_json(path: impl AsRef<Path>) -> Result<StateDict, NnError> {
+        StateDict::from_json(&fs::read_to_string(path).map_err(io_error)?)
+    }
+}
+
+fn format_error(msg: &str) -> NnError {
+    NnError::Format(msg.to_string())
+}
+
+fn io_error(e: std::io::Error) -> NnError {
+    NnError::Io(e.to_string())
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Little-endian reader over a binary file, shared by the state dict and
+/// checkpoint formats. Every read is bounds-checked, and running past the end
+/// is reported as a truncated `what`.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    what: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], what: &'static str) -> Reader<'a> {
+        Reader {
+            bytes,
+            pos: 0,
+            what,
+        }
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], NnError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| format_error(&format!("truncated {}", self.what)))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], NnError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    /// A `u64` byte length, then that many bytes.
+    pub(crate) fn blob(&mut self) -> Result<&'a [u8], NnError> {
+        let len = u64::from_le_bytes(self.array()?);
+        // A length past usize::MAX cannot fit in the input either.
+        self.take(usize::try_from(len).unwrap_or(usize::MAX))
+    }
+
+    /// Checks that everything has been read.
+    pub(crate) fn finish(&self) -> Result<(), NnError> {
+        if self.pos != self.bytes.len() {
+            return Err(format_error(&format!("trailing bytes in {}", self.what)));
+        }
+        Ok(())
+    }
+}
+
+/// Just enough JSON for the state dict format: objects, strings and
+/// numbers.
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &str) -> NnError {
+        format_error(&format!("{} at byte {}", msg, self.pos))
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.s.len() && self.s.as_bytes()[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), NnError> {
+        self.skip_ws();
+        if self.s.as_bytes().get(self.pos) != Some(&c) {
+            return Err(self.error(&format!("expected '{}'", c as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Calls `field` on every key, with the parser at its value.
+    fn object(
+        &mut self,
+        mut field: impl FnMut(&mut Self, String) -> Result<(), NnError>,
+    ) -> Result<(), NnError> {
+        self.expect(b'{')?;
+        self.skip_ws();
+        if self.s.as_bytes().get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            field(self, key)?;
+            self.skip_ws();
+            match self.s.as_bytes().get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, NnError> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let c = self.s[self.pos..]
+                .chars()
+                .next()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let e = *self
+                        .s
+                        .as_bytes()
+                        .get(self.pos)
+                        .ok_or_else(|| self.error("bad escape"))?;
+                    self.pos += 1;
+                    match e {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'n' => out.push('\n'),
+                        b't' => out.push('\t'),
+                        b'r' => out.push('\r'),
+                        b'u' => {
+                            let hex = self
+                                .s
+                                .get(self.pos..self.pos + 4)
+                                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("bad \\u escape"))?;
+                            self.pos += 4;
+                            out.push(hex);
+                        }
+                        _ => return Err(self.error("bad escape")),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, NnError> {
+        self.skip_ws();
+        let start = self.pos;
+        while self.pos < self.s.len()
+            && matches!(
+                self.s.as_bytes()[self.pos],
+                b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'
+            )
+        {
+            self.pos += 1;
+        }
+        self.s[start..self.pos]
+            .parse()
+            .ok()
+            .ok_or_else(|| self.error("expected a number"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> StateDict {
+        let mut state = StateDict::new();
+        state.insert("layers.0.neurons.0.w0".to_string(), 0.1);
+        state.insert("layers.0.neurons.0.b".to_string(), -1e-300);
+        state.insert("layers.1.neurons.0.w0".to_string(), 12345.678901234567);
+        state.insert("odd \"name\"\\\u{1}".to_string(), f64::MIN_POSITIVE);
+        state
+    }
+
+    fn bits(state: &StateDict) -> Vec<(String, u64)> {
+        state
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_bits()))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_get() {
+        let mut state = sample();
+        assert_eq!(state.len(), 4);
+        state.insert("layers.0.neurons.0.w0".to_string(), 2.0);
+        assert_eq!(state.len(), 4);
+        assert_eq!(state.get("layers.0.neurons.0.w0"), Some(2.0));
+        assert_eq!(state.get("nope"), None);
+        assert_eq!(state.keys().next(), Some("layers.0.neurons.0.w0"));
+    }
+
+    #[test]
+    fn test_bytes() {
+        let state = sample();
+        let bytes = state.to_bytes();
+        assert_eq!(&bytes[..4], b"NNSD");
+        assert_eq!(bytes[4..8], 1u32.to_le_bytes());
+        assert_eq!(bytes[8..16], 4u64.to_le_bytes());
+        assert_eq!(bytes[16..20], 21u32.to_le_bytes());
+        assert_eq!(&bytes[20..41], b"layers.0.neurons.0.w0");
+        assert_eq!(bytes[41..49], 0.1f64.to_le_bytes());
+        assert_eq!(bits(&StateDict::from_bytes(&bytes).unwrap()), bits(&state));
+
+        // NaN survives the binary format.
+        let mut nan = StateDict::new();
+        nan.insert("x".to_string(), f64::NAN);
+        let back = StateDict::from_bytes(&nan.to_bytes()).unwrap();
+        assert!(back.get("x").unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_bad_bytes() {
+        let bytes = sample().to_bytes();
+        let err = |b: &[u8]| StateDict::from_bytes(b).unwrap_err();
+        assert!(matches!(err(b"NOPE\x01\0\0\0"), NnError::Format(_)));
+        assert!(matches!(err(&bytes[..bytes.len() - 1]), NnError::Format(_)));
+        assert!(matches!(
+            err(&[&bytes[..], b"x"].concat()),
+            NnError::Format(_)
+        ));
+
+        let mut v2 = bytes.clone();
+        v2[4] = 2;
+        assert_eq!(err(&v2), NnError::UnsupportedVersion(2));
+
+        // A huge count must fail on truncation, not allocate.
+        let mut huge = bytes[..16].to_vec();
+        huge[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(err(&huge), NnError::Format(_)));
+
+        let mut dup = StateDict::new();
+        dup.insert("a".to_string(), 1.0);
+        let mut bytes = dup.to_bytes();
+        bytes[8] = 2;
+        let entry = bytes[16..29].to_vec();
+        bytes.extend_from_slice(&entry);
+        assert!(matches!(err(&bytes), NnError::Format(m) if m.contains("duplicate")));
+    }
+
+    #[test]
+    fn test_json() {
+        let state = sample();
+        let json = state.to_json().unwrap();
+        assert!(json.starts_with("{\n  \"version\": 1,\n  \"parameters\": {\n"));
+        assert!(json.contains("    \"layers.0.neurons.0.w0\": 0.1,\n"));
+        assert!(json.contains("\"odd \\\"name\\\"\\\\\\u0001\""));
+        assert_eq!(bits(&StateDict::from_json(&json).unwrap()), bits(&state));
+
+        let empty = StateDict::new().to_json().unwrap();
+        assert_eq!(StateDict::from_json(&empty).unwrap(), StateDict::new());
+
+        let compact = r#"{"parameters":{"a":1,"b":-2.5E-3},"version":1}"#;
+        let parsed = StateDict::from_json(compact).unwrap();
+        assert_eq!(parsed.get("a"), Some(1.0));
+        assert_eq!(parsed.get("b"), Some(-2.5e-3));
+
+        let mut inf = StateDict::new();
+        inf.insert("x".to_string(), f64::INFINITY);
+        assert!(inf.to_json().is_err());
+    }
+
+    #[test]
+    fn test_bad_json() {
+        let err = |s: &str| StateDict::from_json(s).unwrap_err();
+        assert_eq!(
+            err(r#"{"version": 2, "parameters": {}}"#),
+            NnError::UnsupportedVersion(2)
+        );
+        for bad in [
+            r#"{"parameters": {}}"#,
+            r#"{"version": 1}"#,
+            r#"{"version": 1, "parameters": {"a": 1,}}"#,
+            r#"{"version": 1, "parameters": {"a": "1"}}"#,
+            r#"{"version": 1, "parameters": {"a": 1, "a": 2}}"#,
+            r#"{"version": 1, "parameters": {}, "extra": 1}"#,
+            r#"{"version": 1, "parameters": {}} x"#,
+            r#"{"version": 1, "parameters": {"a"#,
+        ] {
+            assert!(matches!(err(bad), NnError::Format(_)), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_files() {
+        let dir = std::env::temp_dir();
+        let bin = dir.join(format!("nn-state-{}.nnsd", std::process::id()));
+        let json = dir.join(format!("nn-state-{}.json", std::process::id()));
+        let state = sample();
+        state.save(&bin).unwrap();
+        state.save_json(&json).unwrap();
+        assert_eq!(bits(&StateDict::load(&bin).unwrap()), bits(&state));
+        assert_eq!(bits(&StateDict::load_json(&json).unwrap()), bits(&state));
+        fs::remove_file(&bin).unwrap();
+        fs::remove_file(&json).unwrap();
+        assert!(matches!(StateDict::load(&bin), Err(NnError::Io(_))));
+    }
+}
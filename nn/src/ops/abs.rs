@@ -0,0 +1,80 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn abs_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let data_x = x.get_data();
+    // Subgradient 0 at the kink.
+    let sign = if data_x > 0.0 {
+        1.0
+    } else if data_x < 0.0 {
+        -1.0
+    } else {
+        0.0
+    };
+
+    x.set_grad(x.get_grad() + sign * out.get_grad());
+
+    debug!(
+        "abs_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    /// `|x|`, with gradient 0 at `x == 0`.
+    pub fn abs(self) -> Value {
+        let mut out = Value::new(
+            self.get_data().abs(),
+            vec![self.into_child()],
+            "abs".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(abs_backward);
+        out
+    }
+
+    /// -1, 0 or 1 depending on the sign of `x`. Piecewise constant, so no
+    /// gradient flows back.
+    pub fn sign(self) -> Value {
+        let x = self.get_data();
+        let sign = if x > 0.0 {
+            1.0
+        } else if x < 0.0 {
+            -1.0
+        } else {
+            0.0
+        };
+        Value::new(
+            sign,
+            vec![self.into_child()],
+            "sign".to_string(),
+            "".to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_abs() {
+        check_unary(Value::abs, f64::abs, &[-3.0, -0.5, 0.7, 4.0]);
+
+        let x = Value::newd(0.0, "x".to_string());
+        let out = x.clone().abs();
+        out.backward();
+        assert_eq!(out.get_data(), 0.0);
+        assert_eq!(x.get_grad(), 0.0);
+    }
+
+    #[test]
+    fn test_sign() {
+        check_unary(Value::sign, f64::signum, &[-3.0, -0.5, 0.7, 4.0]);
+        assert_eq!(Value::newd(0.0, "x".to_string()).sign().get_data(), 0.0);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::tensor::tensor::Tensor;
+use crate::tensor::value::Value;
+
+/// Elementwise nonlinearity applied to a neuron's pre-activation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Activation {
+    Identity,
+    Relu,
+    LeakyRelu(f64),
+    Elu(f64),
+    Selu,
+    Tanh,
+    HardTanh,
+    Sigmoid,
+    /// Exact GELU.
+    Gelu,
+    /// GELU using the tanh approximation.
+    GeluTanh,
+    Silu,
+    Mish,
+    Softplus,
+}
+
+impl Activation {
+    pub fn apply(&self, x: Value) -> Value {
+        match *self {
+            Activation::Identity => x,
+            Activation::Relu => x.relu(),
+            Activation::LeakyRelu(alpha) => x.leaky_relu(alpha),
+            Activation::Elu(alpha) => x.elu(alpha),
+            Activation::Selu => x.selu(),
+            Activation::Tanh => x.tanh(),
+            Activation::HardTanh => x.hard_tanh(),
+            Activation::Sigmoid => x.sigmoid(),
+            Activation::Gelu => x.gelu(),
+            Activation::GeluTanh => x.gelu_tanh(),
+            Activation::Silu => x.silu(),
+            Activation::Mish => x.mish(),
+            Activation::Softplus => x.softplus(),
+        }
+    }
+
+    /// [`apply`](Activation::apply) to every element of `x`. ReLU and tanh
+    /// use their tensor kernels, the rest go through the scalar ops.
+    pub fn apply_tensor(&self, x: Tensor) -> Tensor {
+        match *self {
+            Activation::Identity => x,
+            Activation::Relu => x.relu(),
+            Activation::Tanh => x.tanh(),
+            act => x.pointwise(&format!("{:?}", act), |x| act.apply(x)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::assert_close;
+    #[test]
+    fn test_apply() {
+        let acts = [
+            (Activation::Identity, 0.5),
+            (Activation::Relu, 0.5),
+            (Activation::LeakyRelu(0.1), 0.5),
+            (Activation::Elu(1.0), 0.5),
+            (Activation::Tanh, f64::tanh(0.5)),
+            (Activation::HardTanh, 0.5),
+            (Activation::Sigmoid, 1.0 / (1.0 + f64::exp(-0.5))),
+            (Activation::Softplus, f64::ln(1.0 + f64::exp(0.5))),
+        ];
+        for (act, expected) in acts {
+            let out = act.apply(Value::newd(0.5, "x".to_string()));
+            assert_close(out.get_data(), expected);
+        }
+    }
+
+    #[test]
+    fn test_apply_tensor() {
+        let acts = [
+            Activation::Identity,
+            Activation::Relu,
+            Activation::LeakyRelu(0.1),
+            Activation::Elu(1.0),
+            Activation::Selu,
+            Activation::Tanh,
+            Activation::HardTanh,
+            Activation::Sigmoid,
+            Activation::Gelu,
+            Activation::GeluTanh,
+            Activation::Silu,
+            Activation::Mish,
+            Activation::Softplus,
+        ];
+        let xs = [-2.0, -0.5, 0.0, 0.3, 1.7];
+        for act in acts {
+            let x = Tensor::newd(xs.to_vec(), &[5], "x".to_string());
+            let y = act.apply_tensor(x.clone());
+            y.backward();
+            for (i, &xi) in xs.iter().enumerate() {
+                let v = Value::newd(xi, "x".to_string());
+                let out = act.apply(v.clone());
+                out.backward();
+                assert_close(y.get_data()[i], out.get_data());
+                assert_close(x.get_grad()[i], v.get_grad());
+            }
+        }
+    }
+}
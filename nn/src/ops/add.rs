@@ -2,10 +2,9 @@ use crate::tensor::value::Value;
 use log::debug;
 use std::ops::{Add, AddAssign};
 use std::rc::Rc;
-use std::sync::Arc;
 
 fn add_backward(out: &Value) {
-    let x = out._prev.get(0).unwrap();
+    let x = out._prev.first().unwrap();
     let y = out._prev.get(1).unwrap();
 
     x.set_grad(x.get_grad() + out.get_grad());
@@ -26,10 +25,11 @@ fn add_backward(out: &Value) {
 }
 
 impl Value {
+    #[allow(clippy::should_implement_trait)]
     pub fn add(self, other: Value) -> Value {
         let mut out = Value::new(
             self.get_data() + other.get_data(),
-            vec![Arc::new(self.clone()), Arc::new(other.clone())],
+            vec![self.clone().into_child(), other.clone().into_child()],
             "+".to_string(),
             "".to_string(),
         );
@@ -0,0 +1,91 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn dot_backward(out: &Value) {
+    let n = out._prev.len() / 2;
+    let (xs, ys) = out._prev.split_at(n);
+    let grad_out = out.get_grad();
+
+    for (x, y) in xs.iter().zip(ys.iter()) {
+        let (data_x, data_y) = (x.get_data(), y.get_data());
+        x.set_grad(x.get_grad() + data_y * grad_out);
+        y.set_grad(y.get_grad() + data_x * grad_out);
+    }
+
+    debug!(
+        "dot_backwards({}) {} pairs grad {}",
+        out.get_label(),
+        n,
+        grad_out
+    );
+}
+
+impl Value {
+    /// Dot product `sum(x[i] * y[i])` as a single graph node. `_prev` holds
+    /// all of `x` followed by all of `y`.
+    pub fn dot(x: &[Value], y: &[Value]) -> Value {
+        assert_eq!(x.len(), y.len(), "dot: operands differ in length");
+        let dot = x
+            .iter()
+            .zip(y.iter())
+            .map(|(xi, yi)| xi.get_data() * yi.get_data())
+            .sum();
+        let mut out = Value::new(
+            dot,
+            x.iter()
+                .chain(y.iter())
+                .map(|v| v.clone().into_child())
+                .collect(),
+            "dot".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(dot_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_dot() {
+        let w = Value::vec(&[1.0, -2.0, 3.0]);
+        let x = Value::vec(&[4.0, 5.0, 6.0]);
+
+        let d = Value::dot(&w, &x);
+        assert_eq!(d.get_data(), 12.0);
+        assert_eq!(d._prev.len(), 6);
+
+        let out = d * 2.0;
+        out.backward();
+        for (wi, xi) in w.iter().zip(x.iter()) {
+            assert_eq!(wi.get_grad(), 2.0 * xi.get_data());
+            assert_eq!(xi.get_grad(), 2.0 * wi.get_data());
+        }
+    }
+
+    #[test]
+    fn test_dot_matches_mul_sum() {
+        let w = Value::vec(&[0.5, -1.5]);
+        let x = Value::vec(&[2.0, 3.0]);
+        let d = Value::dot(&w, &x).tanh();
+        d.backward();
+
+        let ww = Value::vec(&[0.5, -1.5]);
+        let xx = Value::vec(&[2.0, 3.0]);
+        let dd = (ww[0].clone() * xx[0].clone() + ww[1].clone() * xx[1].clone()).tanh();
+        dd.backward();
+
+        assert_eq!(d.get_data(), dd.get_data());
+        for (a, b) in w.iter().chain(x.iter()).zip(ww.iter().chain(xx.iter())) {
+            assert_eq!(a.get_grad(), b.get_grad());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dot_length_mismatch() {
+        Value::dot(&Value::vec(&[1.0, 2.0]), &Value::vec(&[1.0]));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+const SELU_ALPHA: f64 = 1.673_263_242_354_377_3;
+const SELU_SCALE: f64 = 1.050_700_987_355_480_5;
+
+fn elu_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let alpha = out._prev.get(1).unwrap().get_data();
+    let scale = out._prev.get(2).unwrap().get_data();
+    // For x <= 0, d/dx scale * alpha * (e^x - 1) = out + scale * alpha.
+    let grad = if x.get_data() > 0.0 {
+        scale
+    } else {
+        out.get_data() + scale * alpha
+    };
+
+    x.set_grad(x.get_grad() + grad * out.get_grad());
+
+    debug!(
+        "elu_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    fn scaled_elu(self, alpha: f64, scale: f64, op: &str) -> Value {
+        let x = self.get_data();
+        let data = if x > 0.0 {
+            scale * x
+        } else {
+            scale * alpha * f64::exp_m1(x)
+        };
+        let alpha = Value::new(alpha, vec![], "".to_string(), "".to_string());
+        let scale = Value::new(scale, vec![], "".to_string(), "".to_string());
+        let mut out = Value::new(
+            data,
+            vec![self.into_child(), alpha.into_child(), scale.into_child()],
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(elu_backward);
+        out
+    }
+
+    /// `x` for `x > 0`, `alpha * (e^x - 1)` otherwise.
+    pub fn elu(self, alpha: f64) -> Value {
+        self.scaled_elu(alpha, 1.0, "elu")
+    }
+
+    /// Self-normalizing ELU with the fixed constants from Klambauer et al.
+    pub fn selu(self) -> Value {
+        self.scaled_elu(SELU_ALPHA, SELU_SCALE, "selu")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_elu() {
+        check_unary(
+            |x| x.elu(1.5),
+            |x| if x > 0.0 { x } else { 1.5 * (x.exp() - 1.0) },
+            &[-3.0, -0.5, 0.7, 4.0],
+        );
+    }
+
+    #[test]
+    fn test_selu() {
+        check_unary(
+            Value::selu,
+            |x| {
+                if x > 0.0 {
+                    SELU_SCALE * x
+                } else {
+                    SELU_SCALE * SELU_ALPHA * (x.exp() - 1.0)
+                }
+            },
+            &[-3.0, -0.5, 0.7, 4.0],
+        );
+    }
+}
@@ -1,9 +1,8 @@
 use crate::tensor::value::Value;
 use log::debug;
 use std::rc::Rc;
-use std::sync::Arc;
 fn exp_backward(out: &Value) {
-    let x = out._prev.get(0).unwrap();
+    let x = out._prev.first().unwrap();
 
     x.set_grad(x.get_grad() + out.get_data() * out.get_grad());
 
@@ -21,7 +20,7 @@ impl Value {
         let exp = f64::exp(x);
         let mut out = Value::new(
             exp,
-            vec![Arc::new(self)],
+            vec![self.into_child()],
             "tanh".to_string(),
             "".to_string(),
         );
@@ -31,6 +30,4 @@ impl Value {
 }
 
 #[cfg(test)]
-mod test {
-    use super::*;
-}
+mod test {}
@@ -0,0 +1,144 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::f64::consts::{FRAC_2_SQRT_PI, PI, SQRT_2};
+use std::rc::Rc;
+
+const GELU_TANH_COEF: f64 = 0.044_715;
+
+/// Error function: Maclaurin series for small |x|, continued fraction for
+/// erfc in the tails. Accurate to ~1e-14.
+fn erf(x: f64) -> f64 {
+    let a = x.abs();
+    if a < 3.0 {
+        let x2 = x * x;
+        let mut term = x;
+        let mut sum = x;
+        let mut n = 0.0;
+        while term.abs() > 1e-17 * sum.abs() {
+            n += 1.0;
+            term *= -x2 / n;
+            sum += term / (2.0 * n + 1.0);
+        }
+        FRAC_2_SQRT_PI * sum
+    } else {
+        let mut frac = a;
+        for k in (1..60).rev() {
+            frac = a + (k as f64 / 2.0) / frac;
+        }
+        let erfc = f64::exp(-a * a) / (PI.sqrt() * frac);
+        (1.0 - erfc).copysign(x)
+    }
+}
+
+/// Standard normal CDF.
+fn phi(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / SQRT_2))
+}
+
+/// Standard normal PDF.
+fn pdf(x: f64) -> f64 {
+    f64::exp(-0.5 * x * x) / (2.0 * PI).sqrt()
+}
+
+fn gelu_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let data_x = x.get_data();
+    let grad = phi(data_x) + data_x * pdf(data_x);
+
+    x.set_grad(x.get_grad() + grad * out.get_grad());
+
+    debug!(
+        "gelu_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+fn gelu_tanh_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let data_x = x.get_data();
+    let k = (2.0 / PI).sqrt();
+    let t = f64::tanh(k * (data_x + GELU_TANH_COEF * data_x.powi(3)));
+    let dinner = k * (1.0 + 3.0 * GELU_TANH_COEF * data_x * data_x);
+    let grad = 0.5 * (1.0 + t) + 0.5 * data_x * (1.0 - t * t) * dinner;
+
+    x.set_grad(x.get_grad() + grad * out.get_grad());
+
+    debug!(
+        "gelu_tanh_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    /// Exact GELU, `x * Phi(x)` with `Phi` the standard normal CDF.
+    pub fn gelu(self) -> Value {
+        let x = self.get_data();
+        let mut out = Value::new(
+            x * phi(x),
+            vec![self.into_child()],
+            "gelu".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(gelu_backward);
+        out
+    }
+
+    /// GELU using the tanh approximation of `Phi`.
+    pub fn gelu_tanh(self) -> Value {
+        let x = self.get_data();
+        let k = (2.0 / PI).sqrt();
+        let t = f64::tanh(k * (x + GELU_TANH_COEF * x.powi(3)));
+        let mut out = Value::new(
+            0.5 * x * (1.0 + t),
+            vec![self.into_child()],
+            "gelu_tanh".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(gelu_tanh_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::{assert_close, check_unary};
+
+    #[test]
+    fn test_erf() {
+        // Reference values from tables of the error function.
+        assert_eq!(erf(0.0), 0.0);
+        assert_close(erf(0.5), 0.520_499_877_813_046_5);
+        assert_close(erf(1.0), 0.842_700_792_949_714_9);
+        assert_close(erf(-2.0), -0.995_322_265_018_952_7);
+        assert_close(erf(3.5), 0.999_999_256_901_627_7);
+    }
+
+    #[test]
+    fn test_gelu() {
+        check_unary(Value::gelu, |x| x * phi(x), &[-4.0, -1.0, 0.0, 0.5, 3.0]);
+        assert_close(
+            Value::newd(1.0, "x".to_string()).gelu().get_data(),
+            0.841_344_746_068_542_9,
+        );
+    }
+
+    #[test]
+    fn test_gelu_tanh() {
+        let k = (2.0 / PI).sqrt();
+        check_unary(
+            Value::gelu_tanh,
+            |x| 0.5 * x * (1.0 + f64::tanh(k * (x + GELU_TANH_COEF * x.powi(3)))),
+            &[-4.0, -1.0, 0.0, 0.5, 3.0],
+        );
+        // Close to the exact GELU.
+        for x in [-2.0, -0.5, 0.5, 2.0] {
+            let approx = Value::newd(x, "x".to_string()).gelu_tanh().get_data();
+            assert!((approx - x * phi(x)).abs() < 1e-3);
+        }
+    }
+}
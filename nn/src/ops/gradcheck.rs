@@ -0,0 +1,121 @@
+//! Gradient checks against central finite differences, shared by the tests
+//! of every op and module.
+
+use crate::tensor::tensor::Tensor;
+use crate::tensor::value::Value;
+
+const EPS: f64 = 1e-6;
+
+pub(crate) fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-5 * (1.0 + b.abs()), "{} != {}", a, b);
+}
+
+pub(crate) fn assert_all_close(a: &[f64], b: &[f64]) {
+    assert_eq!(a.len(), b.len(), "length {} != {}", a.len(), b.len());
+    for (x, y) in a.iter().zip(b.iter()) {
+        assert!((x - y).abs() < 1e-5 * (1.0 + y.abs()), "{:?} != {:?}", a, b);
+    }
+}
+
+/// Central finite difference of `f` at `x`.
+pub(crate) fn numeric_grad(f: impl Fn(f64) -> f64, x: f64) -> f64 {
+    (f(x + EPS) - f(x - EPS)) / (2.0 * EPS)
+}
+
+/// Fixed uneven weights for the loss `sum(w * out)`, so that a gradient
+/// routed to the wrong output shows up.
+fn weights(n: usize) -> Vec<f64> {
+    (0..n)
+        .map(|i| 0.5 + 0.25 * (i % 5) as f64 - 0.1 * (i % 3) as f64)
+        .collect()
+}
+
+/// Checks `op` against the scalar function `f` at every point, both the
+/// forward value and the gradient.
+pub(crate) fn check_unary(op: impl Fn(Value) -> Value, f: impl Fn(f64) -> f64, points: &[f64]) {
+    for &p in points {
+        let x = Value::newd(p, "x".to_string());
+        let out = op(x.clone());
+        assert_close(out.get_data(), f(p));
+
+        // Scale the output so a wrong use of the upstream gradient shows up.
+        let loss = out * 3.0;
+        loss.backward();
+        assert_close(x.get_grad(), 3.0 * numeric_grad(&f, p));
+    }
+}
+
+/// Checks the gradient of every leaf in `leaves` through the loss
+/// `sum(w * f())`. `f` rebuilds the outputs from the current leaf data.
+pub(crate) fn check_grad(f: impl Fn() -> Vec<Value>, leaves: &[&Value]) {
+    let out = f();
+    let w = weights(out.len());
+    let loss = Value::dot(
+        &out,
+        &w.iter()
+            .map(|w| Value::newd(*w, "".to_string()))
+            .collect::<Vec<_>>(),
+    );
+    leaves.iter().for_each(|l| l.zero_grad());
+    loss.backward();
+
+    let eval = || -> f64 { f().iter().zip(&w).map(|(o, w)| o.get_data() * w).sum() };
+    for leaf in leaves {
+        let x = leaf.get_data();
+        let numeric = numeric_grad(
+            |v| {
+                leaf.set_data(v);
+                eval()
+            },
+            x,
+        );
+        leaf.set_data(x);
+        assert!(
+            (leaf.get_grad() - numeric).abs() < 1e-5 * (1.0 + numeric.abs()),
+            "gradient of {}: {} != {}",
+            leaf.get_label(),
+            leaf.get_grad(),
+            numeric
+        );
+    }
+}
+
+fn leaves(inputs: &[(Vec<f64>, Vec<usize>)]) -> Vec<Tensor> {
+    inputs
+        .iter()
+        .map(|(d, s)| Tensor::newd(d.clone(), s, "x".to_string()))
+        .collect()
+}
+
+/// Checks the gradient of `f` with respect to every input tensor through
+/// the loss `sum(w * f(inputs))`.
+pub(crate) fn check_tensor_grad(
+    f: impl Fn(&[Tensor]) -> Tensor,
+    inputs: &[(Vec<f64>, Vec<usize>)],
+) {
+    let xs = leaves(inputs);
+    let out = f(&xs);
+    let w = weights(out.numel());
+    let loss = (out.clone() * Tensor::newd(w.clone(), out.shape(), "w".to_string())).sum();
+    loss.backward();
+
+    let eval = |inputs: &[(Vec<f64>, Vec<usize>)]| -> f64 {
+        let out = f(&leaves(inputs));
+        out.get_data().iter().zip(&w).map(|(o, w)| o * w).sum()
+    };
+    for (k, x) in xs.iter().enumerate() {
+        let numeric: Vec<f64> = (0..x.numel())
+            .map(|i| {
+                numeric_grad(
+                    |v| {
+                        let mut moved = inputs.to_vec();
+                        moved[k].0[i] = v;
+                        eval(&moved)
+                    },
+                    inputs[k].0[i],
+                )
+            })
+            .collect();
+        assert_all_close(&x.get_grad(), &numeric);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn hard_tanh_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let data_x = x.get_data();
+    if data_x > -1.0 && data_x < 1.0 {
+        x.set_grad(x.get_grad() + out.get_grad());
+    }
+
+    debug!(
+        "hard_tanh_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    /// `x` clamped to `[-1, 1]`. The gradient is 0 outside `(-1, 1)`,
+    /// including at the kinks.
+    pub fn hard_tanh(self) -> Value {
+        let mut out = Value::new(
+            self.get_data().clamp(-1.0, 1.0),
+            vec![self.into_child()],
+            "hard_tanh".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(hard_tanh_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_hard_tanh() {
+        check_unary(
+            Value::hard_tanh,
+            |x| x.clamp(-1.0, 1.0),
+            &[-3.0, -0.5, 0.0, 0.7, 4.0],
+        );
+
+        let x = Value::newd(1.0, "x".to_string());
+        let out = x.clone().hard_tanh();
+        out.backward();
+        assert_eq!(x.get_grad(), 0.0);
+    }
+}
@@ -0,0 +1,579 @@
+//! Elementwise kernels over contiguous `f64`/`f32` buffers.
+//!
+//! Every kernel walks its buffers in fixed-width chunks of [`LANES`]
+//! elements with no branches or calls in the loop body, so the compiler can
+//! keep a chunk in vector registers. `exp` and `tanh` are computed with
+//! polynomial approximations instead of libm calls for the same reason.
+//! [`scalar`] has the same kernels written one element at a time against the
+//! standard library, as a fallback and as the reference the tests compare to.
+//!
+//! Backward kernels accumulate into the gradient buffer they are given.
+
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Elements processed per chunk.
+pub const LANES: usize = 8;
+
+/// Floating point types the kernels run on.
+pub trait Element:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    /// Whether [`exp`] runs chunked for this type. Otherwise it calls the
+    /// standard library, which is faster for `f32`.
+    const CHUNKED_EXP: bool;
+    /// [`tanh`] goes through the standard library for inputs smaller than
+    /// this in magnitude, where the chunked version rounds differently from
+    /// it by more than an ulp.
+    const TANH_SCALAR_BELOW: f64;
+
+    fn to_f64(self) -> f64;
+    fn from_f64(x: f64) -> Self;
+
+    /// Standard library `exp`, used by the scalar path.
+    fn exp(self) -> Self;
+    /// Standard library `tanh`, used by the scalar path.
+    fn tanh(self) -> Self;
+}
+
+impl Element for f64 {
+    const ZERO: f64 = 0.0;
+    const ONE: f64 = 1.0;
+    const CHUNKED_EXP: bool = true;
+    // glibc's tanh is up to 2 ulp off on (-1, 1).
+    const TANH_SCALAR_BELOW: f64 = 1.0;
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn from_f64(x: f64) -> f64 {
+        x
+    }
+    fn exp(self) -> f64 {
+        f64::exp(self)
+    }
+    fn tanh(self) -> f64 {
+        f64::tanh(self)
+    }
+}
+
+impl Element for f32 {
+    const ZERO: f32 = 0.0;
+    const ONE: f32 = 1.0;
+    const CHUNKED_EXP: bool = false;
+    const TANH_SCALAR_BELOW: f64 = 0.0;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    fn from_f64(x: f64) -> f32 {
+        x as f32
+    }
+    fn exp(self) -> f32 {
+        f32::exp(self)
+    }
+    // libm's `tanhf` can be off by more than an ulp, going through `f64`
+    // rounds correctly in practice.
+    fn tanh(self) -> f32 {
+        f64::tanh(self as f64) as f32
+    }
+}
+
+// Shapes of loop the kernels are built from. Each runs whole chunks as
+// fixed-size arrays, then the remainder.
+
+#[inline(always)]
+fn map<T: Element>(x: &[T], out: &mut [T], f: impl Fn(T) -> T) {
+    assert_eq!(x.len(), out.len(), "kernel: length mismatch");
+    let mut xs = x.chunks_exact(LANES);
+    let mut outs = out.chunks_exact_mut(LANES);
+    for (x, out) in (&mut xs).zip(&mut outs) {
+        let x: &[T; LANES] = x.try_into().unwrap();
+        let out: &mut [T; LANES] = out.try_into().unwrap();
+        for (o, x) in out.iter_mut().zip(x) {
+            *o = f(*x);
+        }
+    }
+    for (o, x) in outs.into_remainder().iter_mut().zip(xs.remainder()) {
+        *o = f(*x);
+    }
+}
+
+#[inline(always)]
+fn zip_map<T: Element>(a: &[T], b: &[T], out: &mut [T], f: impl Fn(T, T) -> T) {
+    assert!(
+        a.len() == out.len() && b.len() == out.len(),
+        "kernel: length mismatch"
+    );
+    let (mut as_, mut bs) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let mut outs = out.chunks_exact_mut(LANES);
+    for ((a, b), out) in (&mut as_).zip(&mut bs).zip(&mut outs) {
+        let a: &[T; LANES] = a.try_into().unwrap();
+        let b: &[T; LANES] = b.try_into().unwrap();
+        let out: &mut [T; LANES] = out.try_into().unwrap();
+        for ((o, a), b) in out.iter_mut().zip(a).zip(b) {
+            *o = f(*a, *b);
+        }
+    }
+    let rest = as_.remainder().iter().zip(bs.remainder());
+    for (o, (a, b)) in outs.into_remainder().iter_mut().zip(rest) {
+        *o = f(*a, *b);
+    }
+}
+
+/// `acc[i] = f(acc[i], a[i], b[i])`.
+#[inline(always)]
+fn update<T: Element>(a: &[T], b: &[T], acc: &mut [T], f: impl Fn(T, T, T) -> T) {
+    assert!(
+        a.len() == acc.len() && b.len() == acc.len(),
+        "kernel: length mismatch"
+    );
+    let (mut as_, mut bs) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let mut accs = acc.chunks_exact_mut(LANES);
+    for ((a, b), acc) in (&mut as_).zip(&mut bs).zip(&mut accs) {
+        let a: &[T; LANES] = a.try_into().unwrap();
+        let b: &[T; LANES] = b.try_into().unwrap();
+        let acc: &mut [T; LANES] = acc.try_into().unwrap();
+        for ((c, a), b) in acc.iter_mut().zip(a).zip(b) {
+            *c = f(*c, *a, *b);
+        }
+    }
+    let rest = as_.remainder().iter().zip(bs.remainder());
+    for (c, (a, b)) in accs.into_remainder().iter_mut().zip(rest) {
+        *c = f(*c, *a, *b);
+    }
+}
+
+const LOG2_E: f64 = std::f64::consts::LOG2_E;
+// ln 2 split so that `n * LN2_HI` is exact for the `n` we see.
+const LN2_HI: f64 = 0.6931471803691238;
+const LN2_LO: f64 = 1.9082149292705877e-10;
+// Adding 1.5 * 2^52 rounds to an integer and leaves it in the low mantissa
+// bits.
+const ROUND: f64 = 6_755_399_441_055_744.0;
+// Taylor coefficients of (e^r - 1 - r) / r^2.
+const EXP_POLY: [f64; 12] = [
+    1.0 / 2.0,
+    1.0 / 6.0,
+    1.0 / 24.0,
+    1.0 / 120.0,
+    1.0 / 720.0,
+    1.0 / 5_040.0,
+    1.0 / 40_320.0,
+    1.0 / 362_880.0,
+    1.0 / 3_628_800.0,
+    1.0 / 39_916_800.0,
+    1.0 / 479_001_600.0,
+    1.0 / 6_227_020_800.0,
+];
+// Inputs beyond this go through the scalar `exp`.
+const EXP_LIMIT: f64 = 708.0;
+
+/// Writes `x = n ln 2 + r` with `|r| <= ln 2 / 2` and returns `2^n` and
+/// `e^r - 1`. Needs `|x| <= EXP_LIMIT`.
+#[inline(always)]
+fn exp_parts(x: f64) -> (f64, f64) {
+    let z = x * LOG2_E + ROUND;
+    let n = z - ROUND;
+    let r = (x - n * LN2_HI) - n * LN2_LO;
+    let scale = f64::from_bits(z.to_bits().wrapping_add(1023) << 52);
+    let mut q = EXP_POLY[EXP_POLY.len() - 1];
+    for c in EXP_POLY[..EXP_POLY.len() - 1].iter().rev() {
+        q = q * r + c;
+    }
+    (scale, r + r * r * q)
+}
+
+#[inline(always)]
+fn exp_lane(x: f64) -> f64 {
+    let (scale, p) = exp_parts(x.clamp(-EXP_LIMIT, EXP_LIMIT));
+    scale * (1.0 + p)
+}
+
+// Rational approximation of tanh on [0, 0.625] from Cephes.
+const TANH_P: [f64; 3] = [-0.9643991794250523, -99.28772310019185, -1614.6876844170845];
+const TANH_Q: [f64; 3] = [112.81167849163293, 2235.4883906010045, 4844.063053251255];
+const TANH_SMALL: f64 = 0.625;
+// tanh rounds to 1 well before this.
+const TANH_CLAMP: f64 = 20.0;
+
+#[inline(always)]
+fn tanh_lane(x: f64) -> f64 {
+    let s = x * x;
+    let p = (TANH_P[0] * s + TANH_P[1]) * s + TANH_P[2];
+    let q = ((s + TANH_Q[0]) * s + TANH_Q[1]) * s + TANH_Q[2];
+    let small = (x + x * s * (p / q)).copysign(x);
+
+    // 1 - 2 / (e^2a + 1), with e^2a - 1 computed directly.
+    let a = x.abs().min(TANH_CLAMP);
+    let (scale, p) = exp_parts(2.0 * a);
+    let em1 = scale * p + (scale - 1.0);
+    let large = (1.0 - 2.0 / (em1 + 2.0)).copysign(x);
+
+    if a < TANH_SMALL {
+        small
+    } else {
+        large
+    }
+}
+
+pub fn add<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+    zip_map(a, b, out, |a, b| a + b);
+}
+
+pub fn sub<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+    zip_map(a, b, out, |a, b| a - b);
+}
+
+pub fn mul<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+    zip_map(a, b, out, |a, b| a * b);
+}
+
+pub fn div<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+    zip_map(a, b, out, |a, b| a / b);
+}
+
+/// Within 1 ulp of the standard library `exp`.
+pub fn exp<T: Element>(x: &[T], out: &mut [T]) {
+    if !T::CHUNKED_EXP {
+        return scalar::exp(x, out);
+    }
+    map(x, out, |x| T::from_f64(exp_lane(x.to_f64())));
+    // Overflow, underflow and NaN.
+    for (o, x) in out.iter_mut().zip(x) {
+        if x.to_f64().abs() > EXP_LIMIT || x.to_f64().is_nan() {
+            *o = x.exp();
+        }
+    }
+}
+
+/// Within 1 ulp of the standard library `tanh`.
+pub fn tanh<T: Element>(x: &[T], out: &mut [T]) {
+    map(x, out, |x| T::from_f64(tanh_lane(x.to_f64())));
+    // NaN, and small inputs where the standard library rounds differently.
+    for (o, x) in out.iter_mut().zip(x) {
+        let a = x.to_f64().abs();
+        if a.is_nan() || a < T::TANH_SCALAR_BELOW {
+            *o = x.tanh();
+        }
+    }
+}
+
+pub fn relu<T: Element>(x: &[T], out: &mut [T]) {
+    map(x, out, |x| if x > T::ZERO { x } else { T::ZERO });
+}
+
+/// `dx += grad`.
+pub fn add_backward<T: Element>(grad: &[T], dx: &mut [T]) {
+    update(grad, grad, dx, |d, g, _| d + g);
+}
+
+/// `dx += grad * other`, where `other` is the other factor.
+pub fn mul_backward<T: Element>(grad: &[T], other: &[T], dx: &mut [T]) {
+    update(grad, other, dx, |d, g, y| d + g * y);
+}
+
+/// `dx += grad * out`, where `out` is the result of `exp`.
+pub fn exp_backward<T: Element>(grad: &[T], out: &[T], dx: &mut [T]) {
+    update(grad, out, dx, |d, g, e| d + g * e);
+}
+
+/// `dx += grad * (1 - out^2)`, where `out` is the result of `tanh`.
+pub fn tanh_backward<T: Element>(grad: &[T], out: &[T], dx: &mut [T]) {
+    update(grad, out, dx, |d, g, t| d + g * (T::ONE - t * t));
+}
+
+/// `dx += grad` where `out`, the result of `relu`, is positive.
+pub fn relu_backward<T: Element>(grad: &[T], out: &[T], dx: &mut [T]) {
+    update(grad, out, dx, |d, g, r| if r > T::ZERO { d + g } else { d });
+}
+
+/// The same kernels, one element at a time.
+pub mod scalar {
+    use super::Element;
+
+    pub fn add<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+        for ((o, a), b) in out.iter_mut().zip(a).zip(b) {
+            *o = *a + *b;
+        }
+    }
+
+    pub fn sub<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+        for ((o, a), b) in out.iter_mut().zip(a).zip(b) {
+            *o = *a - *b;
+        }
+    }
+
+    pub fn mul<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+        for ((o, a), b) in out.iter_mut().zip(a).zip(b) {
+            *o = *a * *b;
+        }
+    }
+
+    pub fn div<T: Element>(a: &[T], b: &[T], out: &mut [T]) {
+        for ((o, a), b) in out.iter_mut().zip(a).zip(b) {
+            *o = *a / *b;
+        }
+    }
+
+    pub fn exp<T: Element>(x: &[T], out: &mut [T]) {
+        for (o, x) in out.iter_mut().zip(x) {
+            *o = x.exp();
+        }
+    }
+
+    pub fn tanh<T: Element>(x: &[T], out: &mut [T]) {
+        for (o, x) in out.iter_mut().zip(x) {
+            *o = x.tanh();
+        }
+    }
+
+    pub fn relu<T: Element>(x: &[T], out: &mut [T]) {
+        for (o, x) in out.iter_mut().zip(x) {
+            *o = if *x > T::ZERO { *x } else { T::ZERO };
+        }
+    }
+
+    pub fn add_backward<T: Element>(grad: &[T], dx: &mut [T]) {
+        for (d, g) in dx.iter_mut().zip(grad) {
+            *d = *d + *g;
+        }
+    }
+
+    pub fn mul_backward<T: Element>(grad: &[T], other: &[T], dx: &mut [T]) {
+        for ((d, g), y) in dx.iter_mut().zip(grad).zip(other) {
+            *d = *d + *g * *y;
+        }
+    }
+
+    pub fn exp_backward<T: Element>(grad: &[T], out: &[T], dx: &mut [T]) {
+        for ((d, g), e) in dx.iter_mut().zip(grad).zip(out) {
+            *d = *d + *g * *e;
+        }
+    }
+
+    pub fn tanh_backward<T: Element>(grad: &[T], out: &[T], dx: &mut [T]) {
+        for ((d, g), t) in dx.iter_mut().zip(grad).zip(out) {
+            *d = *d + *g * (T::ONE - *t * *t);
+        }
+    }
+
+    pub fn relu_backward<T: Element>(grad: &[T], out: &[T], dx: &mut [T]) {
+        for ((d, g), r) in dx.iter_mut().zip(grad).zip(out) {
+            if *r > T::ZERO {
+                *d = *d + *g;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Binary = fn(&[f64], &[f64], &mut [f64]);
+
+    /// Distance in representable values between `a` and `b`.
+    fn ulps64(a: f64, b: f64) -> u64 {
+        let ordered = |x: f64| {
+            let bits = x.to_bits() as i64;
+            if bits < 0 {
+                i64::MIN - bits
+            } else {
+                bits
+            }
+        };
+        if a.is_nan() && b.is_nan() {
+            return 0;
+        }
+        ordered(a).abs_diff(ordered(b))
+    }
+
+    fn ulps32(a: f32, b: f32) -> u64 {
+        let ordered = |x: f32| {
+            let bits = x.to_bits() as i32;
+            if bits < 0 {
+                i32::MIN - bits
+            } else {
+                bits
+            }
+        };
+        if a.is_nan() && b.is_nan() {
+            return 0;
+        }
+        ordered(a).abs_diff(ordered(b)) as u64
+    }
+
+    /// Deterministic inputs spread over `[-scale, scale]`, plus edge cases.
+    /// The length is not a multiple of `LANES`, so the remainder loop runs.
+    fn inputs(scale: f64) -> Vec<f64> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut xs: Vec<f64> = (0..20_003)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let u = (state >> 11) as f64 / (1u64 << 53) as f64;
+                (2.0 * u - 1.0) * scale
+            })
+            .collect();
+        xs.extend([
+            0.0,
+            -0.0,
+            1e-300,
+            -1e-300,
+            0.625,
+            -0.625,
+            708.0,
+            709.7,
+            710.0,
+            -745.0,
+            -746.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+        ]);
+        xs
+    }
+
+    fn max_ulps64(
+        kernel: fn(&[f64], &mut [f64]),
+        reference: fn(&[f64], &mut [f64]),
+        scale: f64,
+    ) -> u64 {
+        let xs = inputs(scale);
+        let (mut a, mut b) = (vec![0.0; xs.len()], vec![0.0; xs.len()]);
+        kernel(&xs, &mut a);
+        reference(&xs, &mut b);
+        a.iter().zip(&b).map(|(a, b)| ulps64(*a, *b)).max().unwrap()
+    }
+
+    fn max_ulps32(
+        kernel: fn(&[f32], &mut [f32]),
+        reference: fn(&[f32], &mut [f32]),
+        scale: f64,
+    ) -> u64 {
+        let xs: Vec<f32> = inputs(scale).iter().map(|x| *x as f32).collect();
+        let (mut a, mut b) = (vec![0.0; xs.len()], vec![0.0; xs.len()]);
+        kernel(&xs, &mut a);
+        reference(&xs, &mut b);
+        a.iter().zip(&b).map(|(a, b)| ulps32(*a, *b)).max().unwrap()
+    }
+
+    #[test]
+    fn test_exp() {
+        for scale in [1e-3, 1.0, 30.0, 750.0] {
+            assert!(max_ulps64(exp, scalar::exp, scale) <= 1);
+            assert!(max_ulps32(exp, scalar::exp, scale) <= 1);
+        }
+        let mut out = [0.0; 4];
+        exp(&[f64::INFINITY, f64::NEG_INFINITY, f64::NAN, 0.0], &mut out);
+        assert_eq!(out[0], f64::INFINITY);
+        assert_eq!(out[1], 0.0);
+        assert!(out[2].is_nan());
+        assert_eq!(out[3], 1.0);
+    }
+
+    #[test]
+    fn test_tanh() {
+        for scale in [1e-3, 1.0, 4.0, 30.0] {
+            assert!(max_ulps32(tanh, scalar::tanh, scale) <= 1);
+            assert!(max_ulps64(tanh, scalar::tanh, scale) <= 1);
+        }
+        let mut out = [0.0; 3];
+        tanh(&[-0.0, f64::INFINITY, f64::NAN], &mut out);
+        assert!(out[0] == 0.0 && out[0].is_sign_negative());
+        assert_eq!(out[1], 1.0);
+        assert!(out[2].is_nan());
+    }
+
+    #[test]
+    fn test_tanh_scalar_range() {
+        // Inputs where glibc's tanh is more than 1.5 ulp off the correctly
+        // rounded result, and the chunked version is not: below
+        // TANH_SCALAR_BELOW the kernel returns glibc's value.
+        let xs: [f64; 4] = [
+            0.380628503724493,
+            -0.40490077581854056,
+            0.4335784445625581,
+            0.5439266839428214,
+        ];
+        let mut out = vec![0.0; xs.len()];
+        tanh(&xs, &mut out);
+        for (o, x) in out.iter().zip(xs) {
+            assert_eq!(o.to_bits(), f64::tanh(x).to_bits());
+        }
+    }
+
+    #[test]
+    fn test_relu() {
+        assert_eq!(max_ulps64(relu, scalar::relu, 10.0), 0);
+        assert_eq!(max_ulps32(relu, scalar::relu, 10.0), 0);
+    }
+
+    #[test]
+    fn test_binary() {
+        let a = inputs(10.0);
+        let b: Vec<f64> = a.iter().rev().map(|x| x + 0.5).collect();
+        let kernels: [(Binary, Binary); 4] = [
+            (add, scalar::add),
+            (sub, scalar::sub),
+            (mul, scalar::mul),
+            (div, scalar::div),
+        ];
+        for (kernel, reference) in kernels {
+            let (mut x, mut y) = (vec![0.0; a.len()], vec![0.0; a.len()]);
+            kernel(&a, &b, &mut x);
+            reference(&a, &b, &mut y);
+            assert!(x.iter().zip(&y).all(|(x, y)| ulps64(*x, *y) == 0));
+        }
+
+        let a32: Vec<f32> = a.iter().map(|x| *x as f32).collect();
+        let b32: Vec<f32> = b.iter().map(|x| *x as f32).collect();
+        let (mut x, mut y) = (vec![0.0; a.len()], vec![0.0; a.len()]);
+        mul(&a32, &b32, &mut x);
+        scalar::mul(&a32, &b32, &mut y);
+        assert!(x.iter().zip(&y).all(|(x, y)| ulps32(*x, *y) == 0));
+    }
+
+    #[test]
+    fn test_backward() {
+        let g = inputs(2.0);
+        let y: Vec<f64> = g.iter().map(|x| (x * 3.0).sin()).collect();
+        let kernels: [(Binary, Binary); 4] = [
+            (mul_backward, scalar::mul_backward),
+            (exp_backward, scalar::exp_backward),
+            (tanh_backward, scalar::tanh_backward),
+            (relu_backward, scalar::relu_backward),
+        ];
+        for (kernel, reference) in kernels {
+            // Accumulates into an existing gradient.
+            let (mut a, mut b) = (vec![0.25; g.len()], vec![0.25; g.len()]);
+            kernel(&g, &y, &mut a);
+            reference(&g, &y, &mut b);
+            assert!(a.iter().zip(&b).all(|(a, b)| ulps64(*a, *b) == 0));
+        }
+
+        let (mut a, mut b) = (vec![1.0; g.len()], vec![1.0; g.len()]);
+        add_backward(&g, &mut a);
+        scalar::add_backward(&g, &mut b);
+        assert!(a.iter().zip(&b).all(|(a, b)| ulps64(*a, *b) == 0));
+
+        let mut dx = [0.0; 3];
+        relu_backward(&[1.0, 2.0, 3.0], &[0.5, 0.0, -1.0], &mut dx);
+        assert_eq!(dx, [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_length_mismatch() {
+        let mut out = [0.0; 2];
+        add(&[1.0, 2.0], &[1.0], &mut out);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn leaky_relu_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let alpha = out._prev.get(1).unwrap().get_data();
+    let slope = if x.get_data() > 0.0 { 1.0 } else { alpha };
+
+    x.set_grad(x.get_grad() + slope * out.get_grad());
+
+    debug!(
+        "leaky_relu_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    /// `x` for `x > 0`, `alpha * x` otherwise. The gradient at 0 is `alpha`.
+    pub fn leaky_relu(self, alpha: f64) -> Value {
+        let x = self.get_data();
+        let alpha = Value::new(alpha, vec![], "".to_string(), "".to_string());
+        let mut out = Value::new(
+            if x > 0.0 { x } else { alpha.get_data() * x },
+            vec![self.into_child(), alpha.into_child()],
+            "leaky_relu".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(leaky_relu_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_leaky_relu() {
+        check_unary(
+            |x| x.leaky_relu(0.1),
+            |x| if x > 0.0 { x } else { 0.1 * x },
+            &[-3.0, -0.5, 0.7, 4.0],
+        );
+
+        let x = Value::newd(0.0, "x".to_string());
+        let out = x.clone().leaky_relu(0.2);
+        out.backward();
+        assert_eq!(out.get_data(), 0.0);
+        assert_eq!(x.get_grad(), 0.2);
+    }
+}
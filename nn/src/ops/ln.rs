@@ -0,0 +1,69 @@
+use crate::tensor::value::Value;
+use log::{debug, warn};
+use std::f64::consts::LN_2;
+use std::rc::Rc;
+
+fn log_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    // d/dx log_b(x) = 1 / (x * ln(b)), with ln(b) stored as the second child.
+    let ln_base = out._prev.get(1).unwrap().get_data();
+
+    x.set_grad(x.get_grad() + out.get_grad() / (x.get_data() * ln_base));
+
+    debug!(
+        "log_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    fn log_base(self, ln_base: f64, op: &str) -> Value {
+        let x = self.get_data();
+        if x <= 0.0 {
+            warn!("{}: argument {} is not positive", op, x);
+        }
+        let ln_base = Value::new(ln_base, vec![], "".to_string(), "".to_string());
+        let mut out = Value::new(
+            x.ln() / ln_base.get_data(),
+            vec![self.into_child(), ln_base.into_child()],
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(log_backward);
+        out
+    }
+
+    /// Natural logarithm. `-inf` at 0 and NaN for negative inputs, with a
+    /// logged warning in both cases.
+    pub fn ln(self) -> Value {
+        self.log_base(1.0, "ln")
+    }
+
+    /// Base 2 logarithm, see [`Value::ln`].
+    pub fn log2(self) -> Value {
+        self.log_base(LN_2, "log2")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_ln() {
+        check_unary(Value::ln, f64::ln, &[0.1, 0.5, 1.0, 2.0, 10.0]);
+        assert_eq!(
+            Value::newd(0.0, "x".to_string()).ln().get_data(),
+            f64::NEG_INFINITY
+        );
+        assert!(Value::newd(-1.0, "x".to_string()).ln().get_data().is_nan());
+    }
+
+    #[test]
+    fn test_log2() {
+        check_unary(Value::log2, f64::log2, &[0.1, 0.5, 1.0, 2.0, 10.0]);
+        assert_eq!(Value::newd(8.0, "x".to_string()).log2().get_data(), 3.0);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+// Gradient goes to the operand that was selected; on a tie it is split
+// evenly between both.
+fn minmax_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let y = out._prev.get(1).unwrap();
+    let grad_out = out.get_grad();
+
+    if x.get_data() == y.get_data() {
+        x.set_grad(x.get_grad() + 0.5 * grad_out);
+        y.set_grad(y.get_grad() + 0.5 * grad_out);
+    } else if out.get_data() == x.get_data() {
+        x.set_grad(x.get_grad() + grad_out);
+    } else {
+        y.set_grad(y.get_grad() + grad_out);
+    }
+
+    debug!(
+        "minmax_backwards({}) label {} grad {} label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad(),
+        y.get_label(),
+        y.get_grad()
+    );
+}
+
+fn clamp_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let lo = out._prev.get(1).unwrap().get_data();
+    let hi = out._prev.get(2).unwrap().get_data();
+    let data_x = x.get_data();
+
+    if data_x > lo && data_x < hi {
+        x.set_grad(x.get_grad() + out.get_grad());
+    }
+
+    debug!(
+        "clamp_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    fn minmax(self, other: Value, data: f64, op: &str) -> Value {
+        let mut out = Value::new(
+            data,
+            vec![self.into_child(), other.into_child()],
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(minmax_backward);
+        out
+    }
+
+    /// Larger of `self` and `other`. On a tie the gradient is split evenly.
+    pub fn max(self, other: Value) -> Value {
+        let data = self.get_data().max(other.get_data());
+        self.minmax(other, data, "max")
+    }
+
+    /// Smaller of `self` and `other`. On a tie the gradient is split evenly.
+    pub fn min(self, other: Value) -> Value {
+        let data = self.get_data().min(other.get_data());
+        self.minmax(other, data, "min")
+    }
+
+    /// `self` restricted to `[lo, hi]`. The gradient passes through inside
+    /// `(lo, hi)` and is 0 elsewhere, including at the boundaries, as for
+    /// [`hard_tanh`](Value::hard_tanh) and [`relu`](Value::relu).
+    pub fn clamp(self, lo: f64, hi: f64) -> Value {
+        assert!(lo <= hi, "clamp: lo {} is greater than hi {}", lo, hi);
+        let lo = Value::new(lo, vec![], "".to_string(), "".to_string());
+        let hi = Value::new(hi, vec![], "".to_string(), "".to_string());
+        let mut out = Value::new(
+            self.get_data().clamp(lo.get_data(), hi.get_data()),
+            vec![self.into_child(), lo.into_child(), hi.into_child()],
+            "clamp".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(clamp_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+
+    #[test]
+    fn test_max() {
+        let c = Value::newd(1.0, "c".to_string());
+        check_unary(|x| x.max(c.clone()), |x| x.max(1.0), &[-3.0, 0.5, 1.5, 4.0]);
+
+        let x = Value::newd(2.0, "x".to_string());
+        let y = Value::newd(-1.0, "y".to_string());
+        let out = x.clone().max(y.clone());
+        out.backward();
+        assert_eq!(out.get_data(), 2.0);
+        assert_eq!(x.get_grad(), 1.0);
+        assert_eq!(y.get_grad(), 0.0);
+    }
+
+    #[test]
+    fn test_min() {
+        let c = Value::newd(1.0, "c".to_string());
+        check_unary(|x| x.min(c.clone()), |x| x.min(1.0), &[-3.0, 0.5, 1.5, 4.0]);
+
+        let x = Value::newd(2.0, "x".to_string());
+        let y = Value::newd(-1.0, "y".to_string());
+        let out = x.clone().min(y.clone());
+        out.backward();
+        assert_eq!(out.get_data(), -1.0);
+        assert_eq!(x.get_grad(), 0.0);
+        assert_eq!(y.get_grad(), 1.0);
+    }
+
+    #[test]
+    fn test_minmax_tie() {
+        let x = Value::newd(1.0, "x".to_string());
+        let y = Value::newd(1.0, "y".to_string());
+        let out = x.clone().max(y.clone());
+        out.backward();
+        assert_eq!(x.get_grad(), 0.5);
+        assert_eq!(y.get_grad(), 0.5);
+    }
+
+    #[test]
+    fn test_clamp() {
+        check_unary(
+            |x| x.clamp(-1.0, 2.0),
+            |x| x.clamp(-1.0, 2.0),
+            &[-3.0, -0.5, 0.7, 4.0],
+        );
+
+        let x = Value::newd(2.0, "x".to_string());
+        let out = x.clone().clamp(-1.0, 2.0);
+        out.backward();
+        assert_eq!(x.get_grad(), 0.0);
+
+        // clamp(-1, 1) and hard_tanh agree everywhere, kinks included.
+        for p in [-2.0, -1.0, -0.3, 0.0, 0.6, 1.0, 1.5] {
+            let (a, b) = (
+                Value::newd(p, "a".to_string()),
+                Value::newd(p, "b".to_string()),
+            );
+            let (ya, yb) = (a.clone().clamp(-1.0, 1.0), b.clone().hard_tanh());
+            ya.backward();
+            yb.backward();
+            assert_eq!(ya.get_data(), yb.get_data());
+            assert_eq!(a.get_grad(), b.get_grad(), "at {}", p);
+        }
+    }
+}
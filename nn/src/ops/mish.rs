@@ -0,0 +1,50 @@
+use crate::ops::sigmoid::sigmoid;
+use crate::ops::softplus::softplus;
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn mish_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let data_x = x.get_data();
+    let t = f64::tanh(softplus(data_x));
+    let grad = t + data_x * (1.0 - t * t) * sigmoid(data_x);
+
+    x.set_grad(x.get_grad() + grad * out.get_grad());
+
+    debug!(
+        "mish_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    /// `x * tanh(softplus(x))`.
+    pub fn mish(self) -> Value {
+        let x = self.get_data();
+        let mut out = Value::new(
+            x * f64::tanh(softplus(x)),
+            vec![self.into_child()],
+            "mish".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(mish_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_mish() {
+        check_unary(
+            Value::mish,
+            |x| x * f64::tanh(f64::ln(1.0 + f64::exp(x))),
+            &[-4.0, -1.0, 0.0, 0.5, 3.0],
+        );
+    }
+}
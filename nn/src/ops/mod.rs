@@ -1,8 +1,30 @@
+mod abs;
+pub mod activation;
 mod add;
 mod div;
+mod dot;
+mod elu;
 mod exp;
+mod gelu;
+#[cfg(test)]
+pub(crate) mod gradcheck;
+mod hard_tanh;
+pub mod kernels;
+mod leaky_relu;
+mod ln;
+mod minmax;
+mod mish;
 mod mul;
 mod pow;
 mod relu;
+mod select;
+mod sigmoid;
+mod silu;
+mod softmax;
+mod softplus;
+mod sqrt;
 mod sub;
+mod sum;
 mod tanh;
+mod tensor;
+mod trig;
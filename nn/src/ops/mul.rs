@@ -2,9 +2,8 @@ use crate::tensor::value::Value;
 use log::debug;
 use std::ops::{Mul, MulAssign};
 use std::rc::Rc;
-use std::sync::Arc;
 fn mul_backward(out: &Value) {
-    let x = out._prev.get(0).unwrap();
+    let x = out._prev.first().unwrap();
     let y = out._prev.get(1).unwrap();
 
     x.set_grad(x.get_grad() + y.get_data() * out.get_grad());
@@ -27,7 +26,7 @@ impl Value {
     fn mul(self, other: Value) -> Value {
         let mut out = Value::new(
             self.get_data() * other.get_data(),
-            vec![Arc::new(self.clone()), Arc::new(other.clone())],
+            vec![self.clone().into_child(), other.clone().into_child()],
             "*".to_string(),
             "".to_string(),
         );
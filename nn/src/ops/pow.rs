@@ -1,49 +1,198 @@
 use crate::tensor::value::Value;
-use log::debug;
+use log::{debug, warn};
 use std::rc::Rc;
-use std::sync::Arc;
 
 fn pow_backward(out: &Value) {
-    let x = out._prev.get(0).unwrap();
+    let x = out._prev.first().unwrap();
     let y = out._prev.get(1).unwrap();
 
     let data_x = x.get_data();
     let data_y = y.get_data();
     let grad_out = out.get_grad();
-    let pow_grad = data_y * data_x.powf(data_y - 1.0) * grad_out;
-    // self.grad += (other * self.data**(other-1)) * out.grad
+    // self.grad += (other * self.data**(other-1)) * out.grad, taken as 0 for
+    // other == 0 where it would be 0 * inf = NaN at self == 0.
+    let pow_grad = if data_y == 0.0 {
+        0.0
+    } else {
+        data_y * data_x.powf(data_y - 1.0) * grad_out
+    };
+    x.set_grad(x.get_grad() + pow_grad);
+
+    // other.grad += ln(self.data) * self.data**other * out.grad, taken as 0
+    // where ln is undefined (self.data <= 0).
+    if data_x > 0.0 {
+        let exp_grad = data_x.ln() * out.get_data() * grad_out;
+        y.set_grad(y.get_grad() + exp_grad);
+    }
+
+    debug!(
+        "pow_backwards({}) label {} grad {} label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad(),
+        y.get_label(),
+        y.get_grad()
+    );
+}
+
+fn powi_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let n = out._prev.get(1).unwrap().get_data() as i32;
+
+    // n * x^(n-1), taken as 0 for n == 0 as in pow_backward.
+    let pow_grad = if n == 0 {
+        0.0
+    } else {
+        n as f64 * x.get_data().powi(n - 1) * out.get_grad()
+    };
     x.set_grad(x.get_grad() + pow_grad);
 
     debug!(
-        "pow_backwards({}) label {} grad {}",
+        "powi_backwards({}) label {} grad {}",
         out.get_label(),
         x.get_label(),
         x.get_grad()
     );
 }
+
 impl Value {
+    /// `self ^ other`, differentiable in both the base and the exponent.
+    ///
+    /// For `self < 0` the result is only real when `other` is an integer;
+    /// otherwise it is NaN and a warning is logged. The exponent gradient
+    /// `ln(self) * self^other` is only defined for `self > 0` and is taken
+    /// as 0 elsewhere. At `self == 0` with `0 < other < 1` or `other < 0`
+    /// the base gradient is infinite, as it is for `f64::powf`; with
+    /// `other == 0` it is 0, as `self^0` is constant.
     pub fn pow(self, other: Value) -> Value {
         let x = self.get_data();
         let o = other.get_data();
         let p = (x).powf(o);
+        if p.is_nan() && !x.is_nan() && !o.is_nan() {
+            warn!("pow: {} ^ {} is not a real number", x, o);
+        }
         let mut out = Value::new(
             p,
-            vec![Arc::new(self.clone()), Arc::new(other.clone())],
+            vec![self.clone().into_child(), other.clone().into_child()],
             "^".to_string(),
             "".to_string(),
         );
         out._backward = Rc::new(pow_backward);
         out
     }
+
+    /// `self ^ other` for a constant exponent, see [`Value::pow`].
     pub fn powf(self, other: f64) -> Value {
-        let out = Value::new(other, vec![], "^".to_string(), "powf".to_string());
+        let out = Value::new(other, vec![], "".to_string(), "".to_string());
         self.pow(out)
     }
+
+    /// `self ^ n` for a constant integer exponent. Defined for every base;
+    /// only the base receives a gradient.
+    pub fn powi(self, n: i32) -> Value {
+        let exponent = Value::new(n as f64, vec![], "".to_string(), "".to_string());
+        let mut out = Value::new(
+            self.get_data().powi(n),
+            vec![self.clone().into_child(), exponent.into_child()],
+            "powi".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(powi_backward);
+        out
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ops::gradcheck::{assert_close, numeric_grad};
+
     #[test]
-    fn test_pow() {}
+    fn test_pow() {
+        for (xd, yd) in [(2.0, 3.0), (0.5, -1.5), (3.0, 0.5), (1.5, 2.7)] {
+            let x = Value::newd(xd, "x".to_string());
+            let y = Value::newd(yd, "y".to_string());
+            let z = x.clone().pow(y.clone());
+            z.backward();
+
+            assert_close(z.get_data(), xd.powf(yd));
+            assert_close(x.get_grad(), numeric_grad(|x| x.powf(yd), xd));
+            assert_close(y.get_grad(), numeric_grad(|y| xd.powf(y), yd));
+        }
+    }
+
+    #[test]
+    fn test_pow_learnable_exponent() {
+        // loss = (2^y - 8)^2 should drive y towards 3.
+        let y = Value::newd(1.0, "y".to_string());
+        for _ in 0..200 {
+            let x = Value::newd(2.0, "x".to_string());
+            let loss = (x.pow(y.clone()) - 8.0).powi(2);
+            y.zero_grad();
+            loss.backward();
+            y.set_data(y.get_data() - 0.01 * y.get_grad());
+        }
+        assert!((y.get_data() - 3.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_pow_domain() {
+        // Negative base with an integer exponent is fine, the exponent just
+        // gets no gradient.
+        let x = Value::newd(-2.0, "x".to_string());
+        let y = Value::newd(3.0, "y".to_string());
+        let z = x.clone().pow(y.clone());
+        z.backward();
+        assert_eq!(z.get_data(), -8.0);
+        assert_eq!(x.get_grad(), 12.0);
+        assert_eq!(y.get_grad(), 0.0);
+
+        // Negative base with a fractional exponent is NaN.
+        let z = Value::newd(-2.0, "x".to_string()).powf(0.5);
+        assert!(z.get_data().is_nan());
+
+        // Zero base: exponent gradient is 0 rather than NaN.
+        let x = Value::newd(0.0, "x".to_string());
+        let y = Value::newd(2.0, "y".to_string());
+        let z = x.clone().pow(y.clone());
+        z.backward();
+        assert_eq!(z.get_data(), 0.0);
+        assert_eq!(x.get_grad(), 0.0);
+        assert_eq!(y.get_grad(), 0.0);
+
+        // Zero base and zero exponent: 0^0 = 1 is constant in the base.
+        let x = Value::newd(0.0, "x".to_string());
+        let z = x.clone().powf(0.0);
+        z.backward();
+        assert_eq!(z.get_data(), 1.0);
+        assert_eq!(x.get_grad(), 0.0);
+
+        // Zero base, negative exponent: infinite, as for f64::powf.
+        let x = Value::newd(0.0, "x".to_string());
+        x.clone().powf(-1.0).backward();
+        assert!(x.get_grad().is_infinite());
+    }
+
+    #[test]
+    fn test_powf() {
+        let x = Value::newd(3.0, "x".to_string());
+        let z = x.clone().powf(2.0);
+        assert_eq!(z._prev[1].get_label(), "");
+        assert_eq!(z._prev[1].get_data(), 2.0);
+        z.backward();
+        assert_eq!(z.get_data(), 9.0);
+        assert_eq!(x.get_grad(), 6.0);
+    }
+
+    #[test]
+    fn test_powi() {
+        for (xd, n) in [(-2.0, 3), (1.5, -2), (0.0, 2), (-0.5, 0), (0.0, 0)] {
+            let x = Value::newd(xd, "x".to_string());
+            let z = x.clone().powi(n);
+            z.backward();
+
+            assert_eq!(z.get_data(), f64::powi(xd, n));
+            assert_close(x.get_grad(), numeric_grad(|x| x.powi(n), xd));
+        }
+    }
 }
@@ -1,9 +1,8 @@
 use crate::tensor::value::Value;
 use log::debug;
 use std::rc::Rc;
-use std::sync::Arc;
 fn relu_backward(out: &Value) {
-    let x = out._prev.get(0).unwrap();
+    let x = out._prev.first().unwrap();
     let t = out.get_data();
     let grad_out = out.get_grad();
     if t > 0.0 {
@@ -18,12 +17,9 @@ fn relu_backward(out: &Value) {
 }
 impl Value {
     pub fn relu(self) -> Value {
-        if self.get_data() < 0.0 {
-            self.set_data(0.0)
-        }
         let mut out = Value::new(
-            self.get_data(),
-            vec![Arc::new(self.clone())],
+            self.get_data().max(0.0),
+            vec![self.clone().into_child()],
             "relu".to_string(),
             "".to_string(),
         );
@@ -35,6 +31,15 @@ impl Value {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ops::gradcheck::check_unary;
     #[test]
-    fn test_relu() {}
+    fn test_relu() {
+        check_unary(Value::relu, |x| x.max(0.0), &[-2.0, -0.5, 0.7, 3.0]);
+
+        // The input node is left untouched.
+        let x = Value::newd(-2.0, "x".to_string());
+        let out = x.clone().relu();
+        assert_eq!(out.get_data(), 0.0);
+        assert_eq!(x.get_data(), -2.0);
+    }
 }
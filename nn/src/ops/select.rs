@@ -0,0 +1,190 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn select_backward(out: &Value) {
+    let a = out._prev.first().unwrap();
+    let b = out._prev.get(1).unwrap();
+    let cond = out._prev.get(2).unwrap().get_data() != 0.0;
+    let chosen = if cond { a } else { b };
+
+    chosen.set_grad(chosen.get_grad() + out.get_grad());
+
+    debug!(
+        "select_backwards({}) label {} grad {}",
+        out.get_label(),
+        chosen.get_label(),
+        chosen.get_grad()
+    );
+}
+
+// The gradient goes to the operand that was selected; on a tie it is split
+// evenly between all the tied operands, as for Value::max and Value::min.
+fn extremum_backward(out: &Value) {
+    let data = out.get_data();
+    let winners = out._prev.iter().filter(|x| x.get_data() == data).count();
+    let grad = out.get_grad() / winners as f64;
+    for x in out._prev.iter().filter(|x| x.get_data() == data) {
+        x.set_grad(x.get_grad() + grad);
+    }
+
+    debug!(
+        "extremum_backwards({}) winners {} grad {}",
+        out.get_label(),
+        winners,
+        grad
+    );
+}
+
+fn arg_extremum(xs: &[Value], better: fn(f64, f64) -> bool) -> usize {
+    assert!(!xs.is_empty(), "argmax/argmin of an empty slice");
+    let mut best = 0;
+    for (i, x) in xs.iter().enumerate().skip(1) {
+        if better(x.get_data(), xs[best].get_data()) {
+            best = i;
+        }
+    }
+    best
+}
+
+impl Value {
+    /// `a` if `cond` holds, otherwise `b`. The gradient flows only to the
+    /// selected operand.
+    pub fn select(cond: bool, a: Value, b: Value) -> Value {
+        let data = if cond { a.get_data() } else { b.get_data() };
+        let cond = Value::new(cond as u8 as f64, vec![], "".to_string(), "".to_string());
+        let mut out = Value::new(
+            data,
+            vec![a.into_child(), b.into_child(), cond.into_child()],
+            "select".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(select_backward);
+        out
+    }
+
+    fn extremum(xs: &[Value], i: usize, op: &str) -> Value {
+        let mut out = Value::new(
+            xs[i].get_data(),
+            xs.iter().map(|x| x.clone().into_child()).collect(),
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(extremum_backward);
+        out
+    }
+
+    /// Largest element of `xs`. The gradient goes to the winner; on a tie
+    /// it is split evenly between the tied elements, as for
+    /// [`Value::max`]. Panics if `xs` is empty.
+    pub fn maximum(xs: &[Value]) -> Value {
+        Value::extremum(xs, Value::argmax(xs), "maximum")
+    }
+
+    /// Smallest element of `xs`, see [`Value::maximum`].
+    pub fn minimum(xs: &[Value]) -> Value {
+        Value::extremum(xs, Value::argmin(xs), "minimum")
+    }
+
+    /// Index of the first largest element of `xs`. Not part of the graph.
+    /// Panics if `xs` is empty.
+    pub fn argmax(xs: &[Value]) -> usize {
+        arg_extremum(xs, |a, b| a > b)
+    }
+
+    /// Index of the first smallest element of `xs`, see [`Value::argmax`].
+    pub fn argmin(xs: &[Value]) -> usize {
+        arg_extremum(xs, |a, b| a < b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let a = Value::newd(2.0, "a".to_string());
+        let b = Value::newd(-3.0, "b".to_string());
+
+        let out = Value::select(true, a.clone(), b.clone()) * 2.0;
+        out.backward();
+        assert_eq!(out.get_data(), 4.0);
+        assert_eq!(a.get_grad(), 2.0);
+        assert_eq!(b.get_grad(), 0.0);
+
+        let out = Value::select(false, a.clone(), b.clone());
+        out.backward();
+        assert_eq!(out.get_data(), -3.0);
+        assert_eq!(b.get_grad(), 1.0);
+    }
+
+    #[test]
+    fn test_argmax() {
+        let xs = Value::vec(&[1.0, 5.0, -2.0, 5.0]);
+        assert_eq!(Value::argmax(&xs), 1);
+        assert_eq!(Value::argmin(&xs), 2);
+    }
+
+    #[test]
+    fn test_maximum() {
+        let xs = Value::vec(&[1.0, 5.0, -2.0, 5.0]);
+        let out = Value::maximum(&xs) * 3.0;
+        out.backward();
+        assert_eq!(out.get_data(), 15.0);
+        let grads: Vec<f64> = xs.iter().map(|x| x.get_grad()).collect();
+        assert_eq!(grads, vec![0.0, 1.5, 0.0, 1.5]);
+    }
+
+    #[test]
+    fn test_maximum_tie_matches_max() {
+        let (a, b) = (
+            Value::newd(2.0, "a".to_string()),
+            Value::newd(2.0, "b".to_string()),
+        );
+        Value::maximum(&[a.clone(), b.clone()]).backward();
+        let (c, d) = (
+            Value::newd(2.0, "c".to_string()),
+            Value::newd(2.0, "d".to_string()),
+        );
+        c.clone().max(d.clone()).backward();
+        assert_eq!((a.get_grad(), b.get_grad()), (c.get_grad(), d.get_grad()));
+        assert_eq!(a.get_grad(), 0.5);
+    }
+
+    #[test]
+    fn test_minimum() {
+        let xs = Value::vec(&[1.0, 5.0, -2.0, 5.0]);
+        let out = Value::minimum(&xs);
+        out.backward();
+        assert_eq!(out.get_data(), -2.0);
+        let grads: Vec<f64> = xs.iter().map(|x| x.get_grad()).collect();
+        assert_eq!(grads, vec![0.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_max_margin_loss() {
+        // Multi-class hinge: sum_j max(0, s_j - s_y + 1) over j != y.
+        let scores = Value::vec(&[2.0, 1.5, -1.0]);
+        let y = 0;
+        let zero = Value::newd(0.0, "".to_string());
+        let loss: Value = (0..scores.len())
+            .filter(|&j| j != y)
+            .map(|j| {
+                let margin = scores[j].clone() - scores[y].clone() + 1.0;
+                Value::maximum(&[zero.clone(), margin])
+            })
+            .sum();
+        loss.backward();
+        assert_eq!(loss.get_data(), 0.5);
+        assert_eq!(scores[0].get_grad(), -1.0);
+        assert_eq!(scores[1].get_grad(), 1.0);
+        assert_eq!(scores[2].get_grad(), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_maximum_empty() {
+        Value::maximum(&[]);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+pub(crate) fn sigmoid(x: f64) -> f64 {
+    if x >= 0.0 {
+        1.0 / (1.0 + f64::exp(-x))
+    } else {
+        let e = f64::exp(x);
+        e / (1.0 + e)
+    }
+}
+
+fn sigmoid_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let s = out.get_data();
+
+    x.set_grad(x.get_grad() + s * (1.0 - s) * out.get_grad());
+
+    debug!(
+        "sigmoid_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    pub fn sigmoid(self) -> Value {
+        let mut out = Value::new(
+            sigmoid(self.get_data()),
+            vec![self.into_child()],
+            "sigmoid".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(sigmoid_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_sigmoid() {
+        check_unary(
+            Value::sigmoid,
+            |x| 1.0 / (1.0 + f64::exp(-x)),
+            &[-3.0, -0.5, 0.0, 0.7, 4.0],
+        );
+        assert_eq!(
+            Value::newd(-800.0, "x".to_string()).sigmoid().get_data(),
+            0.0
+        );
+        assert_eq!(
+            Value::newd(800.0, "x".to_string()).sigmoid().get_data(),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use crate::ops::sigmoid::sigmoid;
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn silu_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+    let data_x = x.get_data();
+    let s = sigmoid(data_x);
+    let grad = s * (1.0 + data_x * (1.0 - s));
+
+    x.set_grad(x.get_grad() + grad * out.get_grad());
+
+    debug!(
+        "silu_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    /// `x * sigmoid(x)`.
+    pub fn silu(self) -> Value {
+        let x = self.get_data();
+        let mut out = Value::new(
+            x * sigmoid(x),
+            vec![self.into_child()],
+            "silu".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(silu_backward);
+        out
+    }
+
+    /// Alias for [`Value::silu`].
+    pub fn swish(self) -> Value {
+        self.silu()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_silu() {
+        check_unary(
+            Value::silu,
+            |x| x / (1.0 + f64::exp(-x)),
+            &[-4.0, -1.0, 0.0, 0.5, 3.0],
+        );
+        assert_eq!(
+            Value::newd(1.5, "x".to_string()).swish().get_data(),
+            Value::newd(1.5, "x".to_string()).silu().get_data()
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+// Softmax and log-softmax compute all their outputs in one hub node, which
+// has the logits as children. Each output has the hub as its only child, so
+// building and backpropagating over n logits is O(n): the hub reads the
+// gradients of all the outputs and passes them to the logits in one go.
+
+/// Softmax of `xs` with the max subtracted first so `exp` cannot overflow.
+fn softmax_data(xs: &[f64]) -> Vec<f64> {
+    let max = xs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let exps: Vec<f64> = xs.iter().map(|x| f64::exp(x - max)).collect();
+    let sum: f64 = exps.iter().sum();
+    exps.iter().map(|e| e / sum).collect()
+}
+
+/// Gradient of the logits of a softmax with probabilities `p`, given the
+/// gradients `g` of its outputs.
+fn softmax_grad(p: &[f64], g: &[f64]) -> Vec<f64> {
+    // d p_i / d x_j = p_i * (delta_ij - p_j)
+    let dot: f64 = p.iter().zip(g).map(|(p, g)| p * g).sum();
+    p.iter().zip(g).map(|(p, g)| p * (g - dot)).collect()
+}
+
+/// Same for a log-softmax.
+fn log_softmax_grad(p: &[f64], g: &[f64]) -> Vec<f64> {
+    // d log p_i / d x_j = delta_ij - p_j
+    let sum: f64 = g.iter().sum();
+    p.iter().zip(g).map(|(p, g)| g - p * sum).collect()
+}
+
+fn outputs(
+    xs: &[Value],
+    data: Vec<f64>,
+    p: Vec<f64>,
+    op: &str,
+    grad: fn(&[f64], &[f64]) -> Vec<f64>,
+) -> Vec<Value> {
+    let outs: Vec<Value> = data
+        .into_iter()
+        .map(|d| Value::new(d, vec![], op.to_string(), "".to_string()))
+        .collect();
+    // Copies sharing the outputs' grads but not their parents, so the hub's
+    // backward does not keep itself alive.
+    let grads = outs.clone();
+    let children = xs.iter().map(|x| x.clone().into_child()).collect();
+    let mut hub = Value::new(0.0, children, op.to_string(), "".to_string());
+    let name = op.to_string();
+    hub._backward = Rc::new(move |hub: &Value| {
+        let g: Vec<f64> = grads.iter().map(Value::get_grad).collect();
+        for (x, d) in hub._prev.iter().zip(grad(&p, &g)) {
+            x.set_grad(x.get_grad() + d);
+        }
+        debug!("{}_backwards({}) {} logits", name, hub.get_label(), p.len());
+    });
+    let hub = hub.into_child();
+    outs.into_iter()
+        .map(|mut out| {
+            out._prev = vec![hub.clone()];
+            out
+        })
+        .collect()
+}
+
+impl Value {
+    /// Softmax over `xs`, numerically stable for large logits.
+    pub fn softmax(xs: &[Value]) -> Vec<Value> {
+        let p = softmax_data(&xs.iter().map(|x| x.get_data()).collect::<Vec<_>>());
+        outputs(xs, p.clone(), p, "softmax", softmax_grad)
+    }
+
+    /// `ln(softmax(xs))` computed as `x_i - max - ln(sum(exp(x_j - max)))`,
+    /// so it stays finite where the probabilities underflow to 0.
+    pub fn log_softmax(xs: &[Value]) -> Vec<Value> {
+        let data: Vec<f64> = xs.iter().map(|x| x.get_data()).collect();
+        let max = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let lse = max + data.iter().map(|x| f64::exp(x - max)).sum::<f64>().ln();
+        let p = softmax_data(&data);
+        let data = data.iter().map(|x| x - lse).collect();
+        outputs(xs, data, p, "log_softmax", log_softmax_grad)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::{assert_close, check_grad};
+
+    fn naive_softmax(xs: &[f64]) -> Vec<f64> {
+        let sum: f64 = xs.iter().map(|x| x.exp()).sum();
+        xs.iter().map(|x| x.exp() / sum).collect()
+    }
+
+    fn check(op: fn(&[Value]) -> Vec<Value>, xs: &[f64]) {
+        let x = Value::vec(xs);
+        check_grad(|| op(&x), &x.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_softmax() {
+        let xs = [1.0, 2.0, -0.5, 0.1];
+        let out = Value::softmax(&Value::vec(&xs));
+        for (o, e) in out.iter().zip(naive_softmax(&xs)) {
+            assert_close(o.get_data(), e);
+        }
+        assert_close(out.iter().map(|o| o.get_data()).sum(), 1.0);
+        check(Value::softmax, &xs);
+    }
+
+    #[test]
+    fn test_log_softmax() {
+        let xs = [1.0, 2.0, -0.5, 0.1];
+        let out = Value::log_softmax(&Value::vec(&xs));
+        for (o, e) in out.iter().zip(naive_softmax(&xs)) {
+            assert_close(o.get_data(), e.ln());
+        }
+        check(Value::log_softmax, &xs);
+    }
+
+    #[test]
+    fn test_softmax_large_logits() {
+        let xs = [1000.0, 1001.0, 1002.0];
+        assert!(naive_softmax(&xs).iter().all(|p| p.is_nan()));
+
+        let shifted = naive_softmax(&[0.0, 1.0, 2.0]);
+        let x = Value::vec(&xs);
+        let out = Value::softmax(&x);
+        for (o, e) in out.iter().zip(shifted.iter()) {
+            assert_close(o.get_data(), *e);
+        }
+        out[2].backward();
+        assert!(x.iter().all(|xi| xi.get_grad().is_finite()));
+        assert_close(x[2].get_grad(), shifted[2] * (1.0 - shifted[2]));
+
+        let log_out = Value::log_softmax(&Value::vec(&[-1000.0, 1000.0]));
+        assert_eq!(log_out[0].get_data(), -2000.0);
+        assert_eq!(log_out[1].get_data(), 0.0);
+    }
+
+    #[test]
+    fn test_softmax_shared_hub() {
+        let x = Value::vec(&[0.3, -1.2, 2.0, 0.7, -0.4]);
+        for out in [Value::softmax(&x), Value::log_softmax(&x)] {
+            // Every output has the same hub as its only child, which has the
+            // logits as children.
+            let hub = &out[0]._prev[0];
+            for o in out.iter() {
+                assert_eq!(o._prev.len(), 1);
+                assert!(std::sync::Arc::ptr_eq(&o._prev[0], hub));
+            }
+            assert_eq!(hub._prev.len(), x.len());
+        }
+        // A weighted sum of the outputs sends each weight through the hub once.
+        check_grad(
+            || {
+                let out = Value::log_softmax(&x);
+                let loss = out
+                    .into_iter()
+                    .enumerate()
+                    .fold(Value::newd(0.0, "".to_string()), |acc, (i, o)| {
+                        acc + o * (i as f64 + 1.0)
+                    });
+                vec![loss]
+            },
+            &x.iter().collect::<Vec<_>>(),
+        );
+    }
+}
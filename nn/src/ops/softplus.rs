@@ -0,0 +1,53 @@
+use crate::ops::sigmoid::sigmoid;
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+/// `ln(1 + e^x)` without overflowing for large `x`.
+pub(crate) fn softplus(x: f64) -> f64 {
+    x.max(0.0) + f64::ln_1p(f64::exp(-x.abs()))
+}
+
+fn softplus_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+
+    x.set_grad(x.get_grad() + sigmoid(x.get_data()) * out.get_grad());
+
+    debug!(
+        "softplus_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    pub fn softplus(self) -> Value {
+        let mut out = Value::new(
+            softplus(self.get_data()),
+            vec![self.into_child()],
+            "softplus".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(softplus_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_softplus() {
+        check_unary(
+            Value::softplus,
+            |x| f64::ln(1.0 + f64::exp(x)),
+            &[-4.0, -1.0, 0.0, 0.5, 3.0],
+        );
+        assert_eq!(
+            Value::newd(1000.0, "x".to_string()).softplus().get_data(),
+            1000.0
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::tensor::value::Value;
+use log::{debug, warn};
+use std::rc::Rc;
+
+fn sqrt_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+
+    x.set_grad(x.get_grad() + 0.5 / out.get_data() * out.get_grad());
+
+    debug!(
+        "sqrt_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    /// Square root. NaN for negative inputs, with a logged warning; the
+    /// gradient at 0 is infinite.
+    pub fn sqrt(self) -> Value {
+        let x = self.get_data();
+        if x < 0.0 {
+            warn!("sqrt: argument {} is negative", x);
+        }
+        let mut out = Value::new(
+            x.sqrt(),
+            vec![self.into_child()],
+            "sqrt".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(sqrt_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_sqrt() {
+        check_unary(Value::sqrt, f64::sqrt, &[0.01, 0.5, 1.0, 4.0, 100.0]);
+        assert!(Value::newd(-1.0, "x".to_string())
+            .sqrt()
+            .get_data()
+            .is_nan());
+    }
+}
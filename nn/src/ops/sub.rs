@@ -2,9 +2,8 @@ use crate::tensor::value::Value;
 use log::debug;
 use std::ops::{Neg, Sub, SubAssign};
 use std::rc::Rc;
-use std::sync::Arc;
 fn sub_backward(out: &Value) {
-    let x = out._prev.get(0).unwrap();
+    let x = out._prev.first().unwrap();
     let y = out._prev.get(1).unwrap();
 
     x.set_grad(x.get_grad() - out.get_grad());
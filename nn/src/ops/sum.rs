@@ -0,0 +1,67 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn sum_backward(out: &Value) {
+    let grad_out = out.get_grad();
+    for x in out._prev.iter() {
+        x.set_grad(x.get_grad() + grad_out);
+    }
+
+    debug!(
+        "sum_backwards({}) {} operands grad {}",
+        out.get_label(),
+        out._prev.len(),
+        grad_out
+    );
+}
+
+impl Value {
+    /// Sum of all `values` as a single graph node, so the backward pass
+    /// visits one node instead of a chain of `values.len()` additions.
+    pub fn sum_all(values: &[Value]) -> Value {
+        let sum = values.iter().map(|v| v.get_data()).sum();
+        let mut out = Value::new(
+            sum,
+            values.iter().map(|v| v.clone().into_child()).collect(),
+            "sum".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(sum_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_sum_all() {
+        let x = Value::newd(1.0, "x".to_string());
+        let y = Value::newd(2.0, "y".to_string());
+        let z = Value::newd(3.0, "z".to_string());
+
+        let s = Value::sum_all(&[x.clone(), y.clone(), z.clone(), x.clone()]);
+        assert_eq!(s.get_data(), 7.0);
+        assert_eq!(s._prev.len(), 4);
+
+        let out = s * 2.0;
+        out.backward();
+        assert_eq!(x.get_grad(), 4.0);
+        assert_eq!(y.get_grad(), 2.0);
+        assert_eq!(z.get_grad(), 2.0);
+    }
+
+    #[test]
+    fn test_sum_trait() {
+        let xs = Value::vec(&[1.0, 2.0, 3.0, 4.0]);
+        let s: Value = xs.iter().cloned().sum();
+        assert_eq!(s.get_data(), 10.0);
+        assert_eq!(s._prev.len(), 4);
+        s.backward();
+        xs.iter().for_each(|x| assert_eq!(x.get_grad(), 1.0));
+
+        let empty: Value = Vec::<Value>::new().into_iter().sum();
+        assert_eq!(empty.get_data(), 0.0);
+    }
+}
@@ -1,14 +1,13 @@
 use crate::tensor::value::Value;
 use log::debug;
 use std::rc::Rc;
-use std::sync::Arc;
 fn tanh_backward(out: &Value) {
-    let x = out._prev.get(0).unwrap();
+    let x = out._prev.first().unwrap();
 
     let t = out.get_data();
     let grad_out = out.get_grad();
 
-    let grad = 1.0 - ((t * t) * grad_out);
+    let grad = (1.0 - t * t) * grad_out;
 
     x.set_grad(x.get_grad() + grad);
 
@@ -26,7 +25,7 @@ impl Value {
         let tanh = f64::tanh(x);
         let mut out = Value::new(
             tanh,
-            vec![Arc::new(self)],
+            vec![self.into_child()],
             "tanh".to_string(),
             "".to_string(),
         );
@@ -38,6 +37,9 @@ impl Value {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ops::gradcheck::check_unary;
     #[test]
-    fn test_tanh() {}
+    fn test_tanh() {
+        check_unary(Value::tanh, f64::tanh, &[-2.0, -0.5, 0.0, 0.7, 3.0]);
+    }
 }
@@ -0,0 +1,390 @@
+use crate::ops::kernels;
+use crate::ops::tensor::broadcast::{broadcast_indices, broadcast_shape, reduce_broadcast};
+use crate::tensor::tensor::Tensor;
+use log::debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::rc::Rc;
+
+// Operands of the same shape as the output are used as is. Otherwise each
+// operand is read through `broadcast_indices`, and its gradient is summed back
+// over the broadcast dimensions with `reduce_broadcast`.
+
+/// Data of `x` expanded to the shape `out`.
+fn expand(x: &Tensor, out: &[usize]) -> Vec<f64> {
+    let data = x.get_data();
+    if x.shape() == out {
+        return data;
+    }
+    broadcast_indices(x.shape(), out)
+        .iter()
+        .map(|&i| data[i])
+        .collect()
+}
+
+/// Accumulates an output-shaped gradient into `x`.
+fn add_broadcast_grad(x: &Tensor, grad: &[f64], out: &[usize]) {
+    if x.shape() == out {
+        x.add_grad(grad);
+    } else {
+        let indices = broadcast_indices(x.shape(), out);
+        x.add_grad(&reduce_broadcast(grad, &indices, x.numel()));
+    }
+}
+
+fn add_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let y = out._prev.get(1).unwrap();
+    let grad_out = out.get_grad();
+
+    add_broadcast_grad(x, &grad_out, out.shape());
+    add_broadcast_grad(y, &grad_out, out.shape());
+
+    debug!("add_backwards({}) shape {:?}", out.get_label(), out.shape());
+}
+
+fn sub_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let y = out._prev.get(1).unwrap();
+    let grad_out = out.get_grad();
+    let neg: Vec<f64> = grad_out.iter().map(|g| -g).collect();
+
+    add_broadcast_grad(x, &grad_out, out.shape());
+    add_broadcast_grad(y, &neg, out.shape());
+
+    debug!("sub_backwards({}) shape {:?}", out.get_label(), out.shape());
+}
+
+fn mul_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let y = out._prev.get(1).unwrap();
+    let grad_out = out.get_grad();
+    let (data_x, data_y) = (expand(x, out.shape()), expand(y, out.shape()));
+
+    let (mut grad_x, mut grad_y) = (vec![0.0; grad_out.len()], vec![0.0; grad_out.len()]);
+    kernels::mul_backward(&grad_out, &data_y, &mut grad_x);
+    kernels::mul_backward(&grad_out, &data_x, &mut grad_y);
+    add_broadcast_grad(x, &grad_x, out.shape());
+    add_broadcast_grad(y, &grad_y, out.shape());
+
+    debug!("mul_backwards({}) shape {:?}", out.get_label(), out.shape());
+}
+
+fn div_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let y = out._prev.get(1).unwrap();
+    let grad_out = out.get_grad();
+    let (data_x, data_y) = (expand(x, out.shape()), expand(y, out.shape()));
+
+    let grad_x: Vec<f64> = grad_out.iter().zip(&data_y).map(|(g, b)| g / b).collect();
+    let grad_y: Vec<f64> = grad_out
+        .iter()
+        .zip(data_x.iter().zip(&data_y))
+        .map(|(g, (a, b))| -g * a / (b * b))
+        .collect();
+    add_broadcast_grad(x, &grad_x, out.shape());
+    add_broadcast_grad(y, &grad_y, out.shape());
+
+    debug!("div_backwards({}) shape {:?}", out.get_label(), out.shape());
+}
+
+// Same conventions as `Value::pow`: the base gradient is 0 where the exponent
+// is 0, and the exponent gradient is 0 where the base is not positive.
+fn pow_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let y = out._prev.get(1).unwrap();
+    let grad_out = out.get_grad();
+    let (data_x, data_y) = (expand(x, out.shape()), expand(y, out.shape()));
+    let data_out = out.get_data();
+
+    let grad_x: Vec<f64> = (0..grad_out.len())
+        .map(|i| match data_y[i] {
+            0.0 => 0.0,
+            y => y * data_x[i].powf(y - 1.0) * grad_out[i],
+        })
+        .collect();
+    let grad_y: Vec<f64> = (0..grad_out.len())
+        .map(|i| {
+            if data_x[i] > 0.0 {
+                data_x[i].ln() * data_out[i] * grad_out[i]
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    add_broadcast_grad(x, &grad_x, out.shape());
+    add_broadcast_grad(y, &grad_y, out.shape());
+
+    debug!("pow_backwards({}) shape {:?}", out.get_label(), out.shape());
+}
+
+impl Tensor {
+    fn binary(
+        self,
+        other: Tensor,
+        op: &str,
+        f: fn(&[f64], &[f64], &mut [f64]),
+        backward: fn(&Tensor),
+    ) -> Tensor {
+        let shape = broadcast_shape(self.shape(), other.shape());
+        let (a, b) = (expand(&self, &shape), expand(&other, &shape));
+        let mut data = vec![0.0; a.len()];
+        f(&a, &b, &mut data);
+        let mut out = Tensor::new(
+            data,
+            &shape,
+            vec![self.into_child(), other.into_child()],
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(backward);
+        out
+    }
+
+    /// Elementwise `self ^ other`, broadcasting like the arithmetic
+    /// operators and differentiable in both arguments, see [`Value::pow`].
+    ///
+    /// [`Value::pow`]: crate::tensor::value::Value::pow
+    pub fn pow(self, other: Tensor) -> Tensor {
+        let powf = |a: &[f64], b: &[f64], out: &mut [f64]| {
+            for ((o, a), b) in out.iter_mut().zip(a).zip(b) {
+                *o = a.powf(*b);
+            }
+        };
+        self.binary(other, "^", powf, pow_backward)
+    }
+
+    pub fn powf(self, other: f64) -> Tensor {
+        self.pow(Tensor::scalar(other))
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($trait:ident, $method:ident, $op:expr, $f:expr, $backward:ident) => {
+        impl $trait<Tensor> for Tensor {
+            type Output = Tensor;
+            fn $method(self, other: Tensor) -> Tensor {
+                self.binary(other, $op, $f, $backward)
+            }
+        }
+        impl $trait<f64> for Tensor {
+            type Output = Tensor;
+            fn $method(self, rhs: f64) -> Tensor {
+                let other = Tensor::scalar(rhs);
+                self.binary(other, $op, $f, $backward)
+            }
+        }
+        impl $trait<Tensor> for f64 {
+            type Output = Tensor;
+            fn $method(self, rhs: Tensor) -> Tensor {
+                let other = Tensor::scalar(self);
+                other.binary(rhs, $op, $f, $backward)
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, "+", kernels::add, add_backward);
+impl_binary_op!(Sub, sub, "-", kernels::sub, sub_backward);
+impl_binary_op!(Mul, mul, "*", kernels::mul, mul_backward);
+impl_binary_op!(Div, div, "/", kernels::div, div_backward);
+
+impl Neg for Tensor {
+    type Output = Tensor;
+    fn neg(self) -> Tensor {
+        self * -1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_tensor_grad;
+    use crate::tensor::value::Value;
+
+    fn pair() -> Vec<(Vec<f64>, Vec<usize>)> {
+        vec![
+            (vec![1.0, -2.0, 3.0, 0.5, 2.0, -1.5], vec![2, 3]),
+            (vec![0.5, 4.0, -1.0, 2.0, -3.0, 1.5], vec![2, 3]),
+        ]
+    }
+
+    #[test]
+    fn test_add() {
+        let x = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0], &[2, 2], "x".to_string());
+        let y = Tensor::newd(vec![10.0, 20.0, 30.0, 40.0], &[2, 2], "y".to_string());
+        let z = x.clone() + y.clone();
+        assert_eq!(z.shape(), &[2, 2]);
+        assert_eq!(z.get_data(), vec![11.0, 22.0, 33.0, 44.0]);
+        assert_eq!((1.0 + x.clone()).get_data(), vec![2.0, 3.0, 4.0, 5.0]);
+        z.backward();
+        assert_eq!(x.get_grad(), vec![1.0; 4]);
+        assert_eq!(y.get_grad(), vec![1.0; 4]);
+        check_tensor_grad(|t| t[0].clone() + t[1].clone(), &pair());
+    }
+
+    #[test]
+    fn test_sub() {
+        let x = Tensor::newd(vec![1.0, 2.0], &[2], "x".to_string());
+        assert_eq!((x.clone() - 1.0).get_data(), vec![0.0, 1.0]);
+        assert_eq!((1.0 - x.clone()).get_data(), vec![0.0, -1.0]);
+        assert_eq!((-x).get_data(), vec![-1.0, -2.0]);
+        check_tensor_grad(|t| t[0].clone() - t[1].clone(), &pair());
+    }
+
+    #[test]
+    fn test_mul() {
+        let x = Tensor::newd(vec![1.0, 2.0], &[2], "x".to_string());
+        assert_eq!((x.clone() * 3.0).get_data(), vec![3.0, 6.0]);
+        check_tensor_grad(|t| t[0].clone() * t[1].clone(), &pair());
+    }
+
+    #[test]
+    fn test_div() {
+        let x = Tensor::newd(vec![1.0, 2.0], &[2], "x".to_string());
+        assert_eq!((x.clone() / 2.0).get_data(), vec![0.5, 1.0]);
+        assert_eq!((2.0 / x).get_data(), vec![2.0, 1.0]);
+        check_tensor_grad(|t| t[0].clone() / t[1].clone(), &pair());
+    }
+
+    #[test]
+    fn test_pow() {
+        let x = Tensor::newd(vec![1.0, 2.0, 3.0], &[3], "x".to_string());
+        assert_eq!(x.powf(2.0).get_data(), vec![1.0, 4.0, 9.0]);
+        check_tensor_grad(
+            |t| t[0].clone().pow(t[1].clone()),
+            &[
+                (vec![1.0, 2.0, 0.5, 3.0, 1.5, 0.7], vec![2, 3]),
+                (vec![2.0, -1.0, 0.5, 1.5, 3.0, -0.5], vec![2, 3]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_pow_zero() {
+        // A zero base and a zero exponent broadcast against each other get
+        // the gradients of `Value::pow`, with no NaN at 0^0.
+        let (xs, ys) = ([0.0, 2.0], [0.0, 1.0, 2.0]);
+        let x = Tensor::newd(xs.to_vec(), &[2, 1], "x".to_string());
+        let y = Tensor::newd(ys.to_vec(), &[3], "y".to_string());
+        let z = x.clone().pow(y.clone());
+        z.backward();
+
+        let xv = Value::vec(&xs);
+        let yv = Value::vec(&ys);
+        let mut data = vec![];
+        for a in xv.iter() {
+            for b in yv.iter() {
+                let p = a.clone().pow(b.clone());
+                p.backward();
+                data.push(p.get_data());
+            }
+        }
+        assert_eq!(z.get_data(), data);
+        assert_eq!(
+            x.get_grad(),
+            xv.iter().map(Value::get_grad).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            y.get_grad(),
+            yv.iter().map(Value::get_grad).collect::<Vec<_>>()
+        );
+        assert!(x
+            .get_grad()
+            .iter()
+            .chain(&y.get_grad())
+            .all(|g| g.is_finite()));
+    }
+
+    #[test]
+    fn test_broadcast_scalar() {
+        let x = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0], &[2, 2], "x".to_string());
+        let s = Tensor::scalar(10.0);
+        let z = x.clone() * s.clone();
+        assert_eq!(z.shape(), &[2, 2]);
+        assert_eq!(z.get_data(), vec![10.0, 20.0, 30.0, 40.0]);
+        z.backward();
+        assert_eq!(x.get_grad(), vec![10.0; 4]);
+        assert_eq!(s.get_grad(), vec![10.0]);
+    }
+
+    #[test]
+    fn test_broadcast_row() {
+        // [2, 3] + [3]: the bias is added to every row.
+        let x = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3], "x".to_string());
+        let b = Tensor::newd(vec![10.0, 20.0, 30.0], &[3], "b".to_string());
+        let z = x.clone() + b.clone();
+        assert_eq!(z.get_data(), vec![11.0, 22.0, 33.0, 14.0, 25.0, 36.0]);
+        (z * x).backward();
+        // d/db sum((x + b) * x) = sum over rows of x
+        assert_eq!(b.get_grad(), vec![5.0, 7.0, 9.0]);
+    }
+
+    #[test]
+    fn test_broadcast_column() {
+        // [2, 3] / [2, 1]: each row divided by its own value.
+        let x = Tensor::newd(vec![2.0, 4.0, 6.0, 3.0, 6.0, 9.0], &[2, 3], "x".to_string());
+        let c = Tensor::newd(vec![2.0, 3.0], &[2, 1], "c".to_string());
+        let z = x / c.clone();
+        assert_eq!(z.shape(), &[2, 3]);
+        assert_eq!(z.get_data(), vec![1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+        z.backward();
+        // d/dc sum(x / c) = -sum(x) / c^2 per row
+        assert_eq!(c.get_grad(), vec![-12.0 / 4.0, -18.0 / 9.0]);
+    }
+
+    #[test]
+    fn test_broadcast_outer() {
+        // [2, 1] - [1, 3] -> [2, 3]
+        let a = Tensor::newd(vec![1.0, 2.0], &[2, 1], "a".to_string());
+        let b = Tensor::newd(vec![10.0, 20.0, 30.0], &[1, 3], "b".to_string());
+        let z = a.clone() - b.clone();
+        assert_eq!(z.shape(), &[2, 3]);
+        assert_eq!(z.get_data(), vec![-9.0, -19.0, -29.0, -8.0, -18.0, -28.0]);
+        z.backward();
+        assert_eq!(a.get_grad(), vec![3.0, 3.0]);
+        assert_eq!(b.get_grad(), vec![-2.0, -2.0, -2.0]);
+    }
+
+    #[test]
+    fn test_broadcast_3d() {
+        // [2, 1, 2] * [3, 1] -> [2, 3, 2]
+        let a = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0], &[2, 1, 2], "a".to_string());
+        let b = Tensor::newd(vec![1.0, 10.0, 100.0], &[3, 1], "b".to_string());
+        let z = a.clone() * b.clone();
+        assert_eq!(z.shape(), &[2, 3, 2]);
+        assert_eq!(
+            z.get_data(),
+            vec![1.0, 2.0, 10.0, 20.0, 100.0, 200.0, 3.0, 4.0, 30.0, 40.0, 300.0, 400.0]
+        );
+        z.backward();
+        assert_eq!(a.get_grad(), vec![111.0; 4]);
+        assert_eq!(b.get_grad(), vec![10.0, 10.0, 10.0]);
+
+        for (sa, sb) in [(vec![2, 1, 2], vec![3, 1]), (vec![3, 1], vec![2, 1, 2])] {
+            let na: usize = sa.iter().product();
+            let nb: usize = sb.iter().product();
+            let da = (0..na).map(|i| 0.5 + i as f64).collect();
+            let db = (0..nb).map(|i| 1.5 - 0.7 * i as f64).collect();
+            let inputs = [(da, sa), (db, sb)];
+            check_tensor_grad(|t| t[0].clone() + t[1].clone(), &inputs);
+            check_tensor_grad(|t| t[0].clone() - t[1].clone(), &inputs);
+            check_tensor_grad(|t| t[0].clone() * t[1].clone(), &inputs);
+            check_tensor_grad(|t| t[0].clone() / t[1].clone(), &inputs);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shape_mismatch() {
+        let _ = Tensor::zeros(&[2, 3]) + Tensor::zeros(&[3, 2]);
+    }
+
+    #[test]
+    fn test_reused_operand() {
+        // x * x + x: gradients from both uses accumulate.
+        let x = Tensor::newd(vec![2.0, -1.0], &[2], "x".to_string());
+        let y = x.clone() * x.clone() + x.clone();
+        y.backward();
+        assert_eq!(x.get_grad(), vec![5.0, -1.0]);
+    }
+}
@@ -0,0 +1,111 @@
+/// Shape of the result of broadcasting `a` against `b` with NumPy rules:
+/// shapes are aligned on their trailing dimensions and each pair of sizes
+/// must be equal or contain a 1. Panics if the shapes are incompatible.
+pub(crate) fn broadcast_shape(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let ndim = a.len().max(b.len());
+    (0..ndim)
+        .map(|d| {
+            let da = dim_from_end(a, ndim - 1 - d);
+            let db = dim_from_end(b, ndim - 1 - d);
+            match (da, db) {
+                (x, y) if x == y => x,
+                (1, y) => y,
+                (x, 1) => x,
+                _ => panic!("cannot broadcast shapes {:?} and {:?}", a, b),
+            }
+        })
+        .collect()
+}
+
+fn dim_from_end(shape: &[usize], i: usize) -> usize {
+    if i < shape.len() {
+        shape[shape.len() - 1 - i]
+    } else {
+        1
+    }
+}
+
+/// For every element of a tensor of shape `out`, in row-major order, the
+/// row-major index of the element of a tensor of shape `shape` that is
+/// broadcast to it.
+pub(crate) fn broadcast_indices(shape: &[usize], out: &[usize]) -> Vec<usize> {
+    let numel: usize = out.iter().product();
+    if shape == out {
+        return (0..numel).collect();
+    }
+    // Strides of `shape` aligned to `out`, 0 along broadcast dimensions.
+    let lead = out.len() - shape.len();
+    let mut strides = vec![0; out.len()];
+    let mut stride = 1;
+    for d in (0..shape.len()).rev() {
+        if shape[d] != 1 {
+            strides[lead + d] = stride;
+        }
+        stride *= shape[d];
+    }
+
+    let mut indices = Vec::with_capacity(numel);
+    let mut index = vec![0; out.len()];
+    let mut flat = 0;
+    for _ in 0..numel {
+        indices.push(flat);
+        for d in (0..out.len()).rev() {
+            index[d] += 1;
+            flat += strides[d];
+            if index[d] < out[d] {
+                break;
+            }
+            flat -= strides[d] * index[d];
+            index[d] = 0;
+        }
+    }
+    indices
+}
+
+/// Gradient of a broadcast input: sums `grad_out` over every output element
+/// that the input element was broadcast to.
+pub(crate) fn reduce_broadcast(grad_out: &[f64], indices: &[usize], numel: usize) -> Vec<f64> {
+    let mut grad = vec![0.0; numel];
+    for (g, &i) in grad_out.iter().zip(indices.iter()) {
+        grad[i] += g;
+    }
+    grad
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_broadcast_shape() {
+        assert_eq!(broadcast_shape(&[2, 3], &[2, 3]), vec![2, 3]);
+        assert_eq!(broadcast_shape(&[2, 3], &[]), vec![2, 3]);
+        assert_eq!(broadcast_shape(&[2, 3], &[3]), vec![2, 3]);
+        assert_eq!(broadcast_shape(&[2, 1], &[1, 3]), vec![2, 3]);
+        assert_eq!(broadcast_shape(&[4, 1, 3], &[2, 1]), vec![4, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_broadcast_shape_incompatible() {
+        broadcast_shape(&[2, 3], &[2]);
+    }
+
+    #[test]
+    fn test_broadcast_indices() {
+        assert_eq!(broadcast_indices(&[3], &[2, 3]), vec![0, 1, 2, 0, 1, 2]);
+        assert_eq!(broadcast_indices(&[2, 1], &[2, 3]), vec![0, 0, 0, 1, 1, 1]);
+        assert_eq!(broadcast_indices(&[], &[2, 2]), vec![0, 0, 0, 0]);
+        assert_eq!(
+            broadcast_indices(&[2, 1, 2], &[2, 2, 2]),
+            vec![0, 1, 0, 1, 2, 3, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_reduce_broadcast() {
+        let indices = broadcast_indices(&[2, 1], &[2, 3]);
+        let grad = reduce_broadcast(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &indices, 2);
+        assert_eq!(grad, vec![6.0, 15.0]);
+    }
+}
@@ -0,0 +1,243 @@
+use rayon::prelude::*;
+
+// Block sizes: a KC x NC panel of B is packed once and shared by every
+// thread, each thread then works on MC rows of C at a time.
+const MC: usize = 64;
+const KC: usize = 256;
+const NC: usize = 512;
+
+// Below this many multiply-adds the work stays on the calling thread.
+const PARALLEL_THRESHOLD: usize = 64 * 64 * 64;
+
+/// `c += op(a) @ op(b)` for row-major buffers, where `op(a)` is `[m, k]` and
+/// `op(b)` is `[k, n]`.
+///
+/// With `trans_a` the buffer `a` holds the `[k, m]` matrix whose transpose
+/// is used, and likewise `b` holds `[n, k]` with `trans_b`, so the backward
+/// pass of a matmul never materializes a transpose.
+#[allow(clippy::too_many_arguments)]
+pub fn gemm(
+    trans_a: bool,
+    trans_b: bool,
+    m: usize,
+    n: usize,
+    k: usize,
+    a: &[f64],
+    b: &[f64],
+    c: &mut [f64],
+) {
+    assert_eq!(
+        a.len(),
+        m * k,
+        "gemm: a has {} elements, not {}",
+        a.len(),
+        m * k
+    );
+    assert_eq!(
+        b.len(),
+        k * n,
+        "gemm: b has {} elements, not {}",
+        b.len(),
+        k * n
+    );
+    assert_eq!(
+        c.len(),
+        m * n,
+        "gemm: c has {} elements, not {}",
+        c.len(),
+        m * n
+    );
+    if m == 0 || n == 0 || k == 0 {
+        return;
+    }
+    let parallel = m * n * k >= PARALLEL_THRESHOLD;
+
+    let mut packed = vec![0.0; KC * NC];
+    for jc in (0..n).step_by(NC) {
+        let nc = NC.min(n - jc);
+        for pc in (0..k).step_by(KC) {
+            let kc = KC.min(k - pc);
+            pack_b(trans_b, k, n, b, pc, kc, jc, nc, &mut packed);
+            let packed = &packed[..kc * nc];
+
+            let rows = |(block, c_block): (usize, &mut [f64])| {
+                let ic = block * MC;
+                let mc = c_block.len() / n;
+                kernel(trans_a, m, k, a, packed, ic, mc, pc, kc, jc, nc, n, c_block);
+            };
+            if parallel {
+                c.par_chunks_mut(MC * n).enumerate().for_each(rows);
+            } else {
+                c.chunks_mut(MC * n).enumerate().for_each(rows);
+            }
+        }
+    }
+}
+
+/// Copies `op(b)[pc..pc + kc, jc..jc + nc]` into `packed` as a row-major
+/// `[kc, nc]` panel.
+#[allow(clippy::too_many_arguments)]
+fn pack_b(
+    trans_b: bool,
+    k: usize,
+    n: usize,
+    b: &[f64],
+    pc: usize,
+    kc: usize,
+    jc: usize,
+    nc: usize,
+    packed: &mut [f64],
+) {
+    for p in 0..kc {
+        let row = &mut packed[p * nc..(p + 1) * nc];
+        if trans_b {
+            for (j, r) in row.iter_mut().enumerate() {
+                *r = b[(jc + j) * k + pc + p];
+            }
+        } else {
+            let start = (pc + p) * n + jc;
+            row.copy_from_slice(&b[start..start + nc]);
+        }
+    }
+}
+
+/// Accumulates `op(a)[ic..ic + mc, pc..pc + kc] @ packed` into the
+/// `[mc, n]` row block `c` at columns `jc..jc + nc`, four rows at a time so
+/// each loaded row of `packed` is reused.
+#[allow(clippy::too_many_arguments)]
+fn kernel(
+    trans_a: bool,
+    m: usize,
+    k: usize,
+    a: &[f64],
+    packed: &[f64],
+    ic: usize,
+    mc: usize,
+    pc: usize,
+    kc: usize,
+    jc: usize,
+    nc: usize,
+    n: usize,
+    c: &mut [f64],
+) {
+    let a_at = |i: usize, p: usize| {
+        if trans_a {
+            a[p * m + i]
+        } else {
+            a[i * k + p]
+        }
+    };
+
+    let mut i = 0;
+    while i + 4 <= mc {
+        let (c0, rest) = c[i * n..(i + 4) * n].split_at_mut(n);
+        let (c1, rest) = rest.split_at_mut(n);
+        let (c2, c3) = rest.split_at_mut(n);
+        let (c0, c1, c2, c3) = (
+            &mut c0[jc..jc + nc],
+            &mut c1[jc..jc + nc],
+            &mut c2[jc..jc + nc],
+            &mut c3[jc..jc + nc],
+        );
+        for p in 0..kc {
+            let a0 = a_at(ic + i, pc + p);
+            let a1 = a_at(ic + i + 1, pc + p);
+            let a2 = a_at(ic + i + 2, pc + p);
+            let a3 = a_at(ic + i + 3, pc + p);
+            let bp = &packed[p * nc..(p + 1) * nc];
+            for j in 0..nc {
+                let bj = bp[j];
+                c0[j] += a0 * bj;
+                c1[j] += a1 * bj;
+                c2[j] += a2 * bj;
+                c3[j] += a3 * bj;
+            }
+        }
+        i += 4;
+    }
+    while i < mc {
+        let ci = &mut c[i * n + jc..i * n + jc + nc];
+        for p in 0..kc {
+            let ai = a_at(ic + i, pc + p);
+            let bp = &packed[p * nc..(p + 1) * nc];
+            for (cj, bj) in ci.iter_mut().zip(bp.iter()) {
+                *cj += ai * bj;
+            }
+        }
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::assert_all_close;
+
+    fn reference(
+        trans_a: bool,
+        trans_b: bool,
+        m: usize,
+        n: usize,
+        k: usize,
+        a: &[f64],
+        b: &[f64],
+    ) -> Vec<f64> {
+        let mut c = vec![0.0; m * n];
+        for i in 0..m {
+            for j in 0..n {
+                for p in 0..k {
+                    let av = if trans_a { a[p * m + i] } else { a[i * k + p] };
+                    let bv = if trans_b { b[j * k + p] } else { b[p * n + j] };
+                    c[i * n + j] += av * bv;
+                }
+            }
+        }
+        c
+    }
+
+    fn filled(len: usize, seed: usize) -> Vec<f64> {
+        (0..len)
+            .map(|i| ((i * 7 + seed * 13) % 17) as f64 / 8.0 - 1.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_gemm_small() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let b = [7.0, 8.0, 9.0, 10.0, 11.0, 12.0];
+        let mut c = vec![0.0; 4];
+        gemm(false, false, 2, 2, 3, &a, &b, &mut c);
+        assert_eq!(c, vec![58.0, 64.0, 139.0, 154.0]);
+
+        // Accumulates into c.
+        gemm(false, false, 2, 2, 3, &a, &b, &mut c);
+        assert_eq!(c, vec![116.0, 128.0, 278.0, 308.0]);
+    }
+
+    #[test]
+    fn test_gemm_transposed() {
+        // Sizes straddle the block sizes and the parallel threshold.
+        for (m, n, k) in [
+            (1, 1, 1),
+            (5, 3, 7),
+            (67, 130, 9),
+            (130, 70, 300),
+            (3, 600, 40),
+        ] {
+            for (trans_a, trans_b) in [(false, false), (true, false), (false, true), (true, true)] {
+                let a = filled(m * k, 1);
+                let b = filled(k * n, 2);
+                let mut c = vec![0.0; m * n];
+                gemm(trans_a, trans_b, m, n, k, &a, &b, &mut c);
+                assert_all_close(&c, &reference(trans_a, trans_b, m, n, k, &a, &b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_gemm_empty() {
+        let mut c = vec![1.0; 6];
+        gemm(false, false, 2, 3, 0, &[], &[], &mut c);
+        assert_eq!(c, vec![1.0; 6]);
+    }
+}
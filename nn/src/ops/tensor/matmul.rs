@@ -0,0 +1,138 @@
+use crate::ops::tensor::gemm::gemm;
+use crate::tensor::tensor::Tensor;
+use log::debug;
+use std::rc::Rc;
+
+fn matmul_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let y = out._prev.get(1).unwrap();
+    let (m, k, n) = (x.shape()[0], x.shape()[1], y.shape()[1]);
+    let grad_out = out.get_grad();
+
+    // dx = grad @ y^T, dy = x^T @ grad
+    let mut grad_x = vec![0.0; m * k];
+    gemm(false, true, m, k, n, &grad_out, &y.get_data(), &mut grad_x);
+    x.add_grad(&grad_x);
+    let mut grad_y = vec![0.0; k * n];
+    gemm(true, false, k, n, m, &x.get_data(), &grad_out, &mut grad_y);
+    y.add_grad(&grad_y);
+
+    debug!(
+        "matmul_backwards({}) [{}, {}] @ [{}, {}]",
+        out.get_label(),
+        m,
+        k,
+        k,
+        n
+    );
+}
+
+impl Tensor {
+    /// Matrix product of a `[m, k]` and a `[k, n]` tensor.
+    pub fn matmul(self, other: Tensor) -> Tensor {
+        assert!(
+            self.ndim() == 2 && other.ndim() == 2 && self.shape()[1] == other.shape()[0],
+            "matmul: cannot multiply {:?} by {:?}",
+            self.shape(),
+            other.shape()
+        );
+        let (m, k, n) = (self.shape()[0], self.shape()[1], other.shape()[1]);
+        let mut data = vec![0.0; m * n];
+        gemm(
+            false,
+            false,
+            m,
+            n,
+            k,
+            &self.get_data(),
+            &other.get_data(),
+            &mut data,
+        );
+        let mut out = Tensor::new(
+            data,
+            &[m, n],
+            vec![self.into_child(), other.into_child()],
+            "matmul".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(matmul_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::{assert_all_close, check_tensor_grad};
+    use crate::tensor::value::Value;
+
+    #[test]
+    fn test_matmul() {
+        let a = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3], "a".to_string());
+        let b = Tensor::newd(
+            vec![7.0, 8.0, 9.0, 10.0, 11.0, 12.0],
+            &[3, 2],
+            "b".to_string(),
+        );
+        let c = a.matmul(b);
+        assert_eq!(c.shape(), &[2, 2]);
+        assert_eq!(c.get_data(), vec![58.0, 64.0, 139.0, 154.0]);
+
+        check_tensor_grad(
+            |t| t[0].clone().matmul(t[1].clone()),
+            &[
+                (vec![1.0, -2.0, 3.0, 0.5, 2.0, -1.5], vec![2, 3]),
+                (
+                    vec![
+                        0.5, 4.0, -1.0, 2.0, -3.0, 1.5, 0.2, 0.1, 1.0, -0.7, 0.3, 2.5,
+                    ],
+                    vec![3, 4],
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_matmul_shape_mismatch() {
+        Tensor::zeros(&[2, 3]).matmul(Tensor::zeros(&[2, 3]));
+    }
+
+    #[test]
+    fn test_dense_layer() {
+        // A layer of 2 neurons over 3 inputs as one matmul plus bias,
+        // against the same computation on scalar Values.
+        let x = [1.0, -2.0, 0.5];
+        let w = [[0.1, -0.3], [0.4, 0.2], [-0.5, 0.6]];
+        let b = [0.05, -0.1];
+
+        let xt = Tensor::newd(x.to_vec(), &[1, 3], "x".to_string());
+        let wt = Tensor::newd(w.concat(), &[3, 2], "w".to_string());
+        let bt = Tensor::newd(b.to_vec(), &[1, 2], "b".to_string());
+        let out = (xt.matmul(wt.clone()) + bt.clone()).tanh();
+        out.clone().sum().backward();
+
+        let xv = Value::vec(&x);
+        let wv: Vec<Vec<Value>> = (0..2)
+            .map(|j| Value::vec(&[w[0][j], w[1][j], w[2][j]]))
+            .collect();
+        let bv = Value::vec(&b);
+        let outv: Vec<Value> = (0..2)
+            .map(|j| (Value::dot(&wv[j], &xv) + bv[j].clone()).tanh())
+            .collect();
+        Value::sum_all(&outv).backward();
+
+        let data: Vec<f64> = outv.iter().map(|o| o.get_data()).collect();
+        assert_all_close(&out.get_data(), &data);
+        let wgrad: Vec<f64> = (0..3)
+            .flat_map(|i| {
+                wv.iter()
+                    .map(move |wj| wj[i].get_grad())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_all_close(&wt.get_grad(), &wgrad);
+        let bgrad: Vec<f64> = bv.iter().map(|b| b.get_grad()).collect();
+        assert_all_close(&bt.get_grad(), &bgrad);
+    }
+}
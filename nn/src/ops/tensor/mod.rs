@@ -0,0 +1,8 @@
+mod binary;
+mod broadcast;
+mod gemm;
+mod matmul;
+mod reduce;
+mod unary;
+mod values;
+mod view;
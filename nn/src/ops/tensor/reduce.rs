@@ -0,0 +1,460 @@
+use crate::ops::tensor::broadcast::broadcast_indices;
+use crate::tensor::tensor::Tensor;
+use log::debug;
+use std::rc::Rc;
+
+// A reduction node has the input as first child and the reduced axes as a
+// constant 1-D tensor as second child. The output is laid out like the input
+// with the reduced axes set to size 1, whether or not they are kept, so
+// `broadcast_indices` maps each input element to its output element.
+
+/// Input shape with the reduced `axes` set to 1.
+fn keepdim_shape(shape: &[usize], axes: &[usize]) -> Vec<usize> {
+    shape
+        .iter()
+        .enumerate()
+        .map(|(d, &s)| if axes.contains(&d) { 1 } else { s })
+        .collect()
+}
+
+/// Input, output index of every input element, number of elements reduced
+/// into each output and the output data and gradient.
+struct Reduction<'a> {
+    x: &'a Tensor,
+    data_x: Vec<f64>,
+    indices: Vec<usize>,
+    count: f64,
+    data_out: Vec<f64>,
+    grad_out: Vec<f64>,
+}
+
+fn reduction(out: &Tensor) -> Reduction<'_> {
+    let x = out._prev.first().unwrap();
+    let axes: Vec<usize> = out._prev[1]
+        .get_data()
+        .iter()
+        .map(|&a| a as usize)
+        .collect();
+    let keep = keepdim_shape(x.shape(), &axes);
+    Reduction {
+        x,
+        data_x: x.get_data(),
+        indices: broadcast_indices(&keep, x.shape()),
+        count: (x.numel() / out.numel().max(1)) as f64,
+        data_out: out.get_data(),
+        grad_out: out.get_grad(),
+    }
+}
+
+impl Reduction<'_> {
+    /// Accumulates `f(input, output, output grad)` into every input element.
+    fn backward(&self, f: impl Fn(f64, f64, f64) -> f64) {
+        let grad: Vec<f64> = self
+            .data_x
+            .iter()
+            .zip(self.indices.iter())
+            .map(|(&x, &o)| f(x, self.data_out[o], self.grad_out[o]))
+            .collect();
+        self.x.add_grad(&grad);
+    }
+}
+
+fn sum_backward(out: &Tensor) {
+    let r = reduction(out);
+    r.backward(|_, _, g| g);
+    debug!("sum_backwards({}) shape {:?}", out.get_label(), r.x.shape());
+}
+
+fn mean_backward(out: &Tensor) {
+    let r = reduction(out);
+    r.backward(|_, _, g| g / r.count);
+    debug!(
+        "mean_backwards({}) shape {:?}",
+        out.get_label(),
+        r.x.shape()
+    );
+}
+
+// The gradient is split evenly between the elements equal to the extremum,
+// as for Value::maximum and Value::minimum.
+fn extremum_backward(out: &Tensor) {
+    let r = reduction(out);
+    let mut ties = vec![0.0; r.data_out.len()];
+    for (&x, &o) in r.data_x.iter().zip(r.indices.iter()) {
+        if x == r.data_out[o] {
+            ties[o] += 1.0;
+        }
+    }
+    let grad: Vec<f64> = r
+        .data_x
+        .iter()
+        .zip(r.indices.iter())
+        .map(|(&x, &o)| {
+            if x == r.data_out[o] {
+                r.grad_out[o] / ties[o]
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    r.x.add_grad(&grad);
+    debug!(
+        "extremum_backwards({}) shape {:?}",
+        out.get_label(),
+        r.x.shape()
+    );
+}
+
+fn var_backward(out: &Tensor) {
+    let r = reduction(out);
+    let mut mean = vec![0.0; r.data_out.len()];
+    for (&x, &o) in r.data_x.iter().zip(r.indices.iter()) {
+        mean[o] += x / r.count;
+    }
+    let grad: Vec<f64> = r
+        .data_x
+        .iter()
+        .zip(r.indices.iter())
+        .map(|(&x, &o)| 2.0 * (x - mean[o]) / r.count * r.grad_out[o])
+        .collect();
+    r.x.add_grad(&grad);
+    debug!("var_backwards({}) shape {:?}", out.get_label(), r.x.shape());
+}
+
+fn norm_backward(out: &Tensor) {
+    let r = reduction(out);
+    // The subgradient at a zero norm is taken as 0.
+    r.backward(|x, n, g| if n > 0.0 { x / n * g } else { 0.0 });
+    debug!(
+        "norm_backwards({}) shape {:?}",
+        out.get_label(),
+        r.x.shape()
+    );
+}
+
+fn logsumexp_backward(out: &Tensor) {
+    let r = reduction(out);
+    r.backward(|x, lse, g| f64::exp(x - lse) * g);
+    debug!(
+        "logsumexp_backwards({}) shape {:?}",
+        out.get_label(),
+        r.x.shape()
+    );
+}
+
+/// Folds every element of `data` (laid out as `shape`) into its output
+/// element in `keep`, starting from `init`.
+fn fold_axes(
+    data: &[f64],
+    shape: &[usize],
+    keep: &[usize],
+    init: f64,
+    fold: impl Fn(f64, f64) -> f64,
+) -> Vec<f64> {
+    let mut out = vec![init; keep.iter().product()];
+    for (&x, &o) in data.iter().zip(broadcast_indices(keep, shape).iter()) {
+        out[o] = fold(out[o], x);
+    }
+    out
+}
+
+impl Tensor {
+    /// Reduction of `self` over `axes` given a function from the data, its
+    /// shape, the keepdim shape and the number of elements per output to the
+    /// output data.
+    fn reduce(
+        self,
+        axes: &[usize],
+        keepdim: bool,
+        op: &str,
+        compute: impl Fn(&[f64], &[usize], &[usize], f64) -> Vec<f64>,
+        backward: fn(&Tensor),
+    ) -> Tensor {
+        for (i, &a) in axes.iter().enumerate() {
+            assert!(
+                a < self.ndim() && !axes[..i].contains(&a),
+                "{}: invalid axes {:?} for shape {:?}",
+                op,
+                axes,
+                self.shape()
+            );
+        }
+        let keep = keepdim_shape(self.shape(), axes);
+        let count = (self.numel() / keep.iter().product::<usize>().max(1)) as f64;
+        let data = compute(&self.get_data(), self.shape(), &keep, count);
+
+        let shape: Vec<usize> = if keepdim {
+            keep
+        } else {
+            self.shape()
+                .iter()
+                .enumerate()
+                .filter(|(d, _)| !axes.contains(d))
+                .map(|(_, &s)| s)
+                .collect()
+        };
+        let axes = Tensor::newd(
+            axes.iter().map(|&a| a as f64).collect(),
+            &[axes.len()],
+            "".to_string(),
+        );
+        let mut out = Tensor::new(
+            data,
+            &shape,
+            vec![self.into_child(), axes.into_child()],
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(backward);
+        out
+    }
+
+    fn all_axes(&self) -> Vec<usize> {
+        (0..self.ndim()).collect()
+    }
+
+    /// Sum of all elements as a 0-dimensional tensor.
+    pub fn sum(self) -> Tensor {
+        let axes = self.all_axes();
+        self.sum_axes(&axes, false)
+    }
+
+    /// Mean of all elements as a 0-dimensional tensor.
+    pub fn mean(self) -> Tensor {
+        let axes = self.all_axes();
+        self.mean_axes(&axes, false)
+    }
+
+    /// Sum over `axes`. With `keepdim` the reduced axes are kept with size 1,
+    /// otherwise they are removed.
+    pub fn sum_axes(self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            "sum",
+            |data, shape, keep, _| fold_axes(data, shape, keep, 0.0, |a, x| a + x),
+            sum_backward,
+        )
+    }
+
+    /// Mean over `axes`, see [`Tensor::sum_axes`].
+    pub fn mean_axes(self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            "mean",
+            |data, shape, keep, n| {
+                let sums = fold_axes(data, shape, keep, 0.0, |a, x| a + x);
+                sums.iter().map(|s| s / n).collect()
+            },
+            mean_backward,
+        )
+    }
+
+    /// Maximum over `axes`. The gradient is split evenly between tied maxima,
+    /// as in [`Value::maximum`](crate::tensor::value::Value::maximum).
+    pub fn max_axes(self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            "max",
+            |data, shape, keep, _| fold_axes(data, shape, keep, f64::NEG_INFINITY, f64::max),
+            extremum_backward,
+        )
+    }
+
+    /// Minimum over `axes`. The gradient is split evenly between tied minima,
+    /// as in [`Value::minimum`](crate::tensor::value::Value::minimum).
+    pub fn min_axes(self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            "min",
+            |data, shape, keep, _| fold_axes(data, shape, keep, f64::INFINITY, f64::min),
+            extremum_backward,
+        )
+    }
+
+    /// Population variance (divided by the number of elements) over `axes`.
+    pub fn var_axes(self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            "var",
+            |data, shape, keep, n| {
+                let sums = fold_axes(data, shape, keep, 0.0, |a, x| a + x);
+                let centered: Vec<f64> = data
+                    .iter()
+                    .zip(broadcast_indices(keep, shape).iter())
+                    .map(|(x, &o)| x - sums[o] / n)
+                    .collect();
+                let squares = fold_axes(&centered, shape, keep, 0.0, |a, x| a + x * x);
+                squares.iter().map(|s| s / n).collect()
+            },
+            var_backward,
+        )
+    }
+
+    /// Euclidean norm over `axes`.
+    pub fn norm_axes(self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            "norm",
+            |data, shape, keep, _| {
+                let squares = fold_axes(data, shape, keep, 0.0, |a, x| a + x * x);
+                squares.iter().map(|s| s.sqrt()).collect()
+            },
+            norm_backward,
+        )
+    }
+
+    /// `ln(sum(exp(x)))` over `axes`, computed with the maximum subtracted
+    /// so it does not overflow.
+    pub fn logsumexp_axes(self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            "logsumexp",
+            |data, shape, keep, _| {
+                let max = fold_axes(data, shape, keep, f64::NEG_INFINITY, f64::max);
+                let shifted: Vec<f64> = data
+                    .iter()
+                    .zip(broadcast_indices(keep, shape).iter())
+                    .map(|(x, &o)| f64::exp(x - max[o]))
+                    .collect();
+                let sums = fold_axes(&shifted, shape, keep, 0.0, |a, x| a + x);
+                sums.iter()
+                    .zip(max.iter())
+                    .map(|(s, m)| if m.is_finite() { m + s.ln() } else { *m })
+                    .collect()
+            },
+            logsumexp_backward,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::{assert_all_close, check_tensor_grad};
+    use crate::tensor::value::Value;
+
+    fn input() -> Vec<(Vec<f64>, Vec<usize>)> {
+        let data = (0..24)
+            .map(|i| ((i * 7) % 11) as f64 * 0.3 - 1.2 + 0.01 * i as f64)
+            .collect();
+        vec![(data, vec![2, 3, 4])]
+    }
+
+    fn x() -> Tensor {
+        Tensor::newd(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3], "x".to_string())
+    }
+
+    const AXES: [&[usize]; 5] = [&[0], &[1], &[2], &[0, 2], &[0, 1, 2]];
+
+    #[test]
+    fn test_sum() {
+        let s = x().sum();
+        assert_eq!(s.shape(), &[] as &[usize]);
+        assert_eq!(s.item(), 21.0);
+
+        let s = x().sum_axes(&[0], false);
+        assert_eq!(s.shape(), &[3]);
+        assert_eq!(s.get_data(), vec![5.0, 7.0, 9.0]);
+        let s = x().sum_axes(&[1], true);
+        assert_eq!(s.shape(), &[2, 1]);
+        assert_eq!(s.get_data(), vec![6.0, 15.0]);
+
+        for axes in AXES {
+            for keepdim in [false, true] {
+                check_tensor_grad(|t| t[0].clone().sum_axes(axes, keepdim), &input());
+            }
+        }
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(x().mean().item(), 3.5);
+        assert_eq!(x().mean_axes(&[1], false).get_data(), vec![2.0, 5.0]);
+        for axes in AXES {
+            check_tensor_grad(|t| t[0].clone().mean_axes(axes, true), &input());
+        }
+    }
+
+    #[test]
+    fn test_max_min() {
+        assert_eq!(x().max_axes(&[0], false).get_data(), vec![4.0, 5.0, 6.0]);
+        assert_eq!(x().min_axes(&[1], true).get_data(), vec![1.0, 4.0]);
+        for axes in AXES {
+            check_tensor_grad(|t| t[0].clone().max_axes(axes, false), &input());
+            check_tensor_grad(|t| t[0].clone().min_axes(axes, false), &input());
+        }
+
+        // Ties split the gradient evenly, as Value::maximum and
+        // Value::minimum do.
+        let t = Tensor::newd(vec![3.0, 1.0, 3.0], &[3], "t".to_string());
+        t.clone().max_axes(&[0], false).backward();
+        assert_eq!(t.get_grad(), vec![0.5, 0.0, 0.5]);
+        let vs = Value::vec(&[3.0, 1.0, 3.0]);
+        Value::maximum(&vs).backward();
+        assert_eq!(
+            t.get_grad(),
+            vs.iter().map(Value::get_grad).collect::<Vec<_>>()
+        );
+
+        let t = Tensor::newd(vec![1.0, 2.0, 1.0, 1.0, 5.0, 6.0], &[2, 3], "t".to_string());
+        t.clone().min_axes(&[1], false).backward();
+        let third = 1.0 / 3.0;
+        assert_eq!(t.get_grad(), vec![0.5, 0.0, 0.5, 1.0, 0.0, 0.0]);
+        let t = Tensor::newd(vec![2.0; 3], &[3], "t".to_string());
+        t.clone().min_axes(&[0], false).backward();
+        assert_eq!(t.get_grad(), vec![third; 3]);
+    }
+
+    #[test]
+    fn test_var() {
+        assert_eq!(x().var_axes(&[1], false).get_data(), vec![2.0 / 3.0; 2]);
+        assert_eq!(x().var_axes(&[0], false).get_data(), vec![2.25; 3]);
+        for axes in AXES {
+            check_tensor_grad(|t| t[0].clone().var_axes(axes, false), &input());
+        }
+    }
+
+    #[test]
+    fn test_norm() {
+        let t = Tensor::newd(vec![3.0, 4.0, 0.0, 0.0], &[2, 2], "t".to_string());
+        let n = t.clone().norm_axes(&[1], false);
+        assert_eq!(n.get_data(), vec![5.0, 0.0]);
+        n.backward();
+        assert_eq!(t.get_grad(), vec![0.6, 0.8, 0.0, 0.0]);
+        for axes in AXES {
+            check_tensor_grad(|t| t[0].clone().norm_axes(axes, true), &input());
+        }
+    }
+
+    #[test]
+    fn test_logsumexp() {
+        let lse = x().logsumexp_axes(&[1], false).get_data();
+        let expected: Vec<f64> = [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]
+            .iter()
+            .map(|r| r.iter().map(|v: &f64| v.exp()).sum::<f64>().ln())
+            .collect();
+        assert_all_close(&lse, &expected);
+
+        // Would overflow without subtracting the maximum.
+        let big = Tensor::newd(vec![1000.0, 1000.0], &[2], "big".to_string());
+        let lse = big.logsumexp_axes(&[0], false);
+        assert_all_close(&lse.get_data(), &[1000.0 + 2f64.ln()]);
+
+        for axes in AXES {
+            check_tensor_grad(|t| t[0].clone().logsumexp_axes(axes, false), &input());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_axis() {
+        x().sum_axes(&[2], false);
+    }
+}
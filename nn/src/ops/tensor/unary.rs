@@ -0,0 +1,153 @@
+use crate::ops::kernels;
+use crate::tensor::tensor::Tensor;
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn exp_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let (grad, data) = (out.get_grad(), out.get_data());
+    x.update_grad(|dx| kernels::exp_backward(&grad, &data, dx));
+
+    debug!("exp_backwards({}) shape {:?}", out.get_label(), out.shape());
+}
+
+fn tanh_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let (grad, data) = (out.get_grad(), out.get_data());
+    x.update_grad(|dx| kernels::tanh_backward(&grad, &data, dx));
+
+    debug!(
+        "tanh_backwards({}) shape {:?}",
+        out.get_label(),
+        out.shape()
+    );
+}
+
+fn relu_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let (grad, data) = (out.get_grad(), out.get_data());
+    x.update_grad(|dx| kernels::relu_backward(&grad, &data, dx));
+
+    debug!(
+        "relu_backwards({}) shape {:?}",
+        out.get_label(),
+        out.shape()
+    );
+}
+
+impl Tensor {
+    fn unary(self, op: &str, f: fn(&[f64], &mut [f64]), backward: fn(&Tensor)) -> Tensor {
+        let x = self.get_data();
+        let mut data = vec![0.0; x.len()];
+        f(&x, &mut data);
+        let shape = self.shape().to_vec();
+        let mut out = Tensor::new(
+            data,
+            &shape,
+            vec![self.into_child()],
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(backward);
+        out
+    }
+
+    pub fn exp(self) -> Tensor {
+        self.unary("exp", kernels::exp, exp_backward)
+    }
+
+    pub fn tanh(self) -> Tensor {
+        self.unary("tanh", kernels::tanh, tanh_backward)
+    }
+
+    pub fn relu(self) -> Tensor {
+        self.unary("relu", kernels::relu, relu_backward)
+    }
+
+    /// `f` applied elementwise through the scalar `Value` ops, for functions
+    /// without a kernel. Each element's derivative is taken from the scalar
+    /// backward during the forward pass and kept for the tensor backward, so
+    /// values and gradients match the scalar path exactly.
+    pub(crate) fn pointwise(self, op: &str, f: impl Fn(Value) -> Value) -> Tensor {
+        let (data, dydx): (Vec<f64>, Vec<f64>) = self
+            .get_data()
+            .into_iter()
+            .map(|x| {
+                let x = Value::newd(x, "".to_string());
+                let y = f(x.clone());
+                y.backward();
+                (y.get_data(), x.get_grad())
+            })
+            .unzip();
+        let shape = self.shape().to_vec();
+        let mut out = Tensor::new(
+            data,
+            &shape,
+            vec![self.into_child()],
+            op.to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(move |out: &Tensor| {
+            let x = out._prev.first().unwrap();
+            let grad = out.get_grad();
+            x.update_grad(|dx| {
+                for ((dx, g), d) in dx.iter_mut().zip(&grad).zip(&dydx) {
+                    *dx += g * d;
+                }
+            });
+
+            debug!(
+                "pointwise_backwards({}) shape {:?}",
+                out.get_label(),
+                out.shape()
+            );
+        });
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::{assert_all_close, check_tensor_grad};
+
+    fn input() -> Vec<(Vec<f64>, Vec<usize>)> {
+        vec![(vec![1.0, -2.0, 0.3, 0.5, 2.0, -1.5], vec![3, 2])]
+    }
+
+    #[test]
+    fn test_exp() {
+        let x = Tensor::newd(vec![0.0, 1.0], &[2], "x".to_string());
+        // The exp kernel is within an ulp of `f64::exp`, not bit-identical.
+        assert_all_close(&x.exp().get_data(), &[1.0, f64::exp(1.0)]);
+        check_tensor_grad(|t| t[0].clone().exp(), &input());
+    }
+
+    #[test]
+    fn test_tanh() {
+        check_tensor_grad(|t| t[0].clone().tanh(), &input());
+    }
+
+    #[test]
+    fn test_relu() {
+        let x = Tensor::newd(vec![-1.0, 2.0], &[2], "x".to_string());
+        assert_eq!(x.clone().relu().get_data(), vec![0.0, 2.0]);
+        assert_eq!(x.get_data(), vec![-1.0, 2.0]);
+        check_tensor_grad(|t| t[0].clone().relu(), &input());
+    }
+
+    #[test]
+    fn test_pointwise() {
+        let x = Tensor::newd(vec![1.0, -2.0, 0.3], &[3], "x".to_string());
+        let y = x.clone().pointwise("sigmoid", Value::sigmoid);
+        let expected: Vec<f64> = x
+            .get_data()
+            .into_iter()
+            .map(|x| Value::newd(x, "".to_string()).sigmoid().get_data())
+            .collect();
+        assert_eq!(y.get_data(), expected);
+        check_tensor_grad(|t| t[0].clone().pointwise("gelu", Value::gelu), &input());
+        check_tensor_grad(|t| t[0].clone().pointwise("elu", |x| x.elu(0.5)), &input());
+    }
+}
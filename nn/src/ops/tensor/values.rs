@@ -0,0 +1,102 @@
+use crate::tensor::tensor::Tensor;
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+impl Tensor {
+    /// A tensor holding the current data of `values`, row-major in `shape`,
+    /// whose gradient is added to theirs on backward. This lets a model keep
+    /// its parameters as `Value`s and still train through tensor ops.
+    pub fn from_values(values: &[Value], shape: &[usize]) -> Tensor {
+        let data = values.iter().map(Value::get_data).collect();
+        let mut out = Tensor::new(data, shape, vec![], "values".to_string(), "".to_string());
+        let values = values.to_vec();
+        out._backward = Rc::new(move |out: &Tensor| {
+            for (v, g) in values.iter().zip(out.get_grad()) {
+                v.set_grad(v.get_grad() + g);
+            }
+            debug!(
+                "values_backwards({}) {} values",
+                out.get_label(),
+                values.len()
+            );
+        });
+        out
+    }
+
+    /// The elements of `self`, row-major, as `Value`s. Backpropagating from
+    /// any of them runs the tensor graph once, seeded with the gradients of
+    /// all of them, so scalar code can build a loss on a tensor's output.
+    pub fn to_values(&self) -> Vec<Value> {
+        let outs: Vec<Value> = self
+            .get_data()
+            .into_iter()
+            .map(|d| Value::new(d, vec![], "tensor".to_string(), "".to_string()))
+            .collect();
+        // Copies sharing the outputs' grads but not their parents, so the
+        // backward closure does not keep itself alive.
+        let grads = outs.clone();
+        let tensor = self.clone();
+        let mut hub = Value::new(0.0, vec![], "tensor".to_string(), "".to_string());
+        hub._backward = Rc::new(move |_: &Value| {
+            let grad: Vec<f64> = grads.iter().map(Value::get_grad).collect();
+            tensor.backward_with(&grad);
+            debug!(
+                "tensor_backwards({}) shape {:?}",
+                tensor.get_label(),
+                tensor.shape()
+            );
+        });
+        let hub = hub.into_child();
+        outs.into_iter()
+            .map(|mut v| {
+                v._prev = vec![hub.clone()];
+                v
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_values() {
+        let vs = Value::vec(&[1.0, -2.0, 3.0, 0.5]);
+        let t = Tensor::from_values(&vs, &[2, 2]);
+        assert_eq!(t.get_data(), [1.0, -2.0, 3.0, 0.5]);
+        let w = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0], &[2, 2], "w".to_string());
+        (t * w).sum().backward();
+        let grads: Vec<f64> = vs.iter().map(Value::get_grad).collect();
+        assert_eq!(grads, [1.0, 2.0, 3.0, 4.0]);
+
+        // Gradients accumulate like any other use of the values.
+        Tensor::from_values(&vs[..1], &[1]).sum().backward();
+        assert_eq!(vs[0].get_grad(), 2.0);
+    }
+
+    #[test]
+    fn test_to_values() {
+        let x = Tensor::newd(vec![1.0, -2.0, 3.0, 0.5], &[2, 2], "x".to_string());
+        let w = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0], &[2, 2], "w".to_string());
+        let ys = (x.clone() * w).to_values();
+        let data: Vec<f64> = ys.iter().map(Value::get_data).collect();
+        assert_eq!(data, [1.0, -4.0, 9.0, 2.0]);
+
+        // A scalar loss over some of the outputs reaches the tensor leaves,
+        // and the tensor graph runs once however many outputs are used.
+        let loss = ys[0].clone() * 2.0 + ys[3].clone() + ys[3].clone();
+        loss.backward();
+        assert_eq!(x.get_grad(), [2.0, 0.0, 0.0, 8.0]);
+
+        // Round trip through both bridges.
+        let vs = Value::vec(&[0.5, 1.5]);
+        let ys = Tensor::from_values(&vs, &[2]).tanh().to_values();
+        Value::sum_all(&ys).backward();
+        for (v, y) in vs.iter().zip(&ys) {
+            let expected = 1.0 - y.get_data() * y.get_data();
+            assert!((v.get_grad() - expected).abs() < 1e-15);
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use crate::tensor::tensor::{contiguous_strides, Tensor};
+use log::debug;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+// Reshape of a contiguous tensor, permute and slice are views over the input's
+// storage. index_select, concat and stack copy. Ops that need a parameter in
+// the backward pass keep it in a trailing constant child, as the scalar ops do.
+
+/// Gradient of a strided view: each output element is matched to the input
+/// element at the same storage position.
+fn view_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let logical: HashMap<usize, usize> = x
+        .storage_indices()
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| (p, i))
+        .collect();
+    let mut grad = vec![0.0; x.numel()];
+    for (g, p) in out.get_grad().iter().zip(out.storage_indices()) {
+        grad[logical[&p]] += g;
+    }
+    x.add_grad(&grad);
+
+    debug!(
+        "view_backwards({}) shape {:?}",
+        out.get_label(),
+        out.shape()
+    );
+}
+
+// Reshape keeps the row-major order of the elements, so the gradient passes
+// through unchanged.
+fn reshape_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    x.add_grad(&out.get_grad());
+
+    debug!(
+        "reshape_backwards({}) shape {:?}",
+        out.get_label(),
+        out.shape()
+    );
+}
+
+/// Sizes before, along and after `dim`.
+fn split_dims(shape: &[usize], dim: usize) -> (usize, usize, usize) {
+    (
+        shape[..dim].iter().product(),
+        shape[dim],
+        shape[dim + 1..].iter().product(),
+    )
+}
+
+fn index_select_backward(out: &Tensor) {
+    let x = out._prev.first().unwrap();
+    let params = out._prev[1].get_data();
+    let dim = params[0] as usize;
+    let (outer, size, inner) = split_dims(x.shape(), dim);
+    let grad_out = out.get_grad();
+
+    let mut grad = vec![0.0; x.numel()];
+    let mut src = grad_out.chunks(inner);
+    for o in 0..outer {
+        for &idx in params[1..].iter() {
+            let start = (o * size + idx as usize) * inner;
+            for (g, s) in grad[start..start + inner]
+                .iter_mut()
+                .zip(src.next().unwrap())
+            {
+                *g += s;
+            }
+        }
+    }
+    x.add_grad(&grad);
+
+    debug!(
+        "index_select_backwards({}) shape {:?}",
+        out.get_label(),
+        out.shape()
+    );
+}
+
+fn concat_backward(out: &Tensor) {
+    let (dim, xs) = out._prev.split_last().unwrap();
+    let dim = dim.get_data()[0] as usize;
+    let (outer, _, inner) = split_dims(out.shape(), dim);
+    let grad_out = out.get_grad();
+
+    let mut grads: Vec<Vec<f64>> = xs.iter().map(|x| Vec::with_capacity(x.numel())).collect();
+    let mut src = grad_out.iter();
+    for _ in 0..outer {
+        for (x, grad) in xs.iter().zip(grads.iter_mut()) {
+            grad.extend(src.by_ref().take(x.shape()[dim] * inner));
+        }
+    }
+    for (x, grad) in xs.iter().zip(grads.iter()) {
+        x.add_grad(grad);
+    }
+
+    debug!(
+        "concat_backwards({}) shape {:?}",
+        out.get_label(),
+        out.shape()
+    );
+}
+
+impl Tensor {
+    /// Same elements with a new shape. A view when `self` is contiguous,
+    /// otherwise a copy.
+    pub fn reshape(self, shape: &[usize]) -> Tensor {
+        assert_eq!(
+            self.numel(),
+            shape.iter().product::<usize>(),
+            "reshape: cannot reshape {:?} to {:?}",
+            self.shape(),
+            shape
+        );
+        let mut out = if self.is_contiguous() {
+            self.view(
+                shape,
+                &contiguous_strides(shape),
+                self.offset(),
+                "reshape".to_string(),
+            )
+        } else {
+            Tensor::new(
+                self.get_data(),
+                shape,
+                vec![self.into_child()],
+                "reshape".to_string(),
+                "".to_string(),
+            )
+        };
+        out._backward = Rc::new(reshape_backward);
+        out
+    }
+
+    /// View with the axes reordered: axis `i` of the result is axis `dims[i]`
+    /// of `self`.
+    pub fn permute(self, dims: &[usize]) -> Tensor {
+        let mut sorted = dims.to_vec();
+        sorted.sort_unstable();
+        assert!(
+            sorted == (0..self.ndim()).collect::<Vec<_>>(),
+            "permute: {:?} is not a permutation of the axes of {:?}",
+            dims,
+            self.shape()
+        );
+        let shape: Vec<usize> = dims.iter().map(|&d| self.shape()[d]).collect();
+        let strides: Vec<usize> = dims.iter().map(|&d| self.strides()[d]).collect();
+        let mut out = self.view(&shape, &strides, self.offset(), "permute".to_string());
+        out._backward = Rc::new(view_backward);
+        out
+    }
+
+    /// View with axes `d0` and `d1` swapped.
+    pub fn transpose(self, d0: usize, d1: usize) -> Tensor {
+        let mut dims: Vec<usize> = (0..self.ndim()).collect();
+        dims.swap(d0, d1);
+        self.permute(&dims)
+    }
+
+    /// View of every `step`-th element in `start..end` along `dim`.
+    pub fn slice(self, dim: usize, start: usize, end: usize, step: usize) -> Tensor {
+        assert!(
+            dim < self.ndim() && start <= end && end <= self.shape()[dim] && step > 0,
+            "slice: invalid range {}..{} step {} on axis {} of {:?}",
+            start,
+            end,
+            step,
+            dim,
+            self.shape()
+        );
+        let mut shape = self.shape().to_vec();
+        let mut strides = self.strides().to_vec();
+        let offset = self.offset() + start * strides[dim];
+        shape[dim] = (end - start).div_ceil(step);
+        strides[dim] *= step;
+        let mut out = self.view(&shape, &strides, offset, "slice".to_string());
+        out._backward = Rc::new(view_backward);
+        out
+    }
+
+    /// View of `len` elements along `dim` starting at `start`.
+    pub fn narrow(self, dim: usize, start: usize, len: usize) -> Tensor {
+        self.slice(dim, start, start + len, 1)
+    }
+
+    /// Copy of the entries at `indices` along `dim`, in that order. Indices
+    /// may repeat.
+    pub fn index_select(self, dim: usize, indices: &[usize]) -> Tensor {
+        assert!(
+            dim < self.ndim(),
+            "index_select: no axis {} in {:?}",
+            dim,
+            self.shape()
+        );
+        let (outer, size, inner) = split_dims(self.shape(), dim);
+        assert!(
+            indices.iter().all(|&i| i < size),
+            "index_select: indices {:?} out of range for size {}",
+            indices,
+            size
+        );
+        let data = self.get_data();
+        let mut selected = Vec::with_capacity(outer * indices.len() * inner);
+        for o in 0..outer {
+            for &idx in indices {
+                let start = (o * size + idx) * inner;
+                selected.extend_from_slice(&data[start..start + inner]);
+            }
+        }
+        let mut shape = self.shape().to_vec();
+        shape[dim] = indices.len();
+
+        let params: Vec<f64> = [dim].iter().chain(indices).map(|&i| i as f64).collect();
+        let params = Tensor::newd(params, &[indices.len() + 1], "".to_string());
+        let mut out = Tensor::new(
+            selected,
+            &shape,
+            vec![self.into_child(), params.into_child()],
+            "index_select".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(index_select_backward);
+        out
+    }
+
+    /// Joins `xs` along the existing axis `dim`. All other axes must match.
+    pub fn concat(xs: &[Tensor], dim: usize) -> Tensor {
+        assert!(!xs.is_empty(), "concat: no tensors");
+        let first = xs[0].shape();
+        assert!(dim < first.len(), "concat: no axis {} in {:?}", dim, first);
+        for x in xs {
+            assert!(
+                x.ndim() == first.len()
+                    && (0..first.len()).all(|d| d == dim || x.shape()[d] == first[d]),
+                "concat: shapes {:?} and {:?} differ outside axis {}",
+                first,
+                x.shape(),
+                dim
+            );
+        }
+        let (outer, _, inner) = split_dims(first, dim);
+        let data: Vec<Vec<f64>> = xs.iter().map(|x| x.get_data()).collect();
+        let mut joined = Vec::with_capacity(data.iter().map(|d| d.len()).sum());
+        for o in 0..outer {
+            for (x, d) in xs.iter().zip(data.iter()) {
+                let len = x.shape()[dim] * inner;
+                joined.extend_from_slice(&d[o * len..(o + 1) * len]);
+            }
+        }
+        let mut shape = first.to_vec();
+        shape[dim] = xs.iter().map(|x| x.shape()[dim]).sum();
+
+        let mut children: Vec<Arc<Tensor>> = xs.iter().map(|x| x.clone().into_child()).collect();
+        children.push(Tensor::newd(vec![dim as f64], &[1], "".to_string()).into_child());
+        let mut out = Tensor::new(
+            joined,
+            &shape,
+            children,
+            "concat".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(concat_backward);
+        out
+    }
+
+    /// Joins `xs`, which must all have the same shape, along a new axis
+    /// inserted at `dim`.
+    pub fn stack(xs: &[Tensor], dim: usize) -> Tensor {
+        assert!(!xs.is_empty(), "stack: no tensors");
+        assert!(
+            dim <= xs[0].ndim(),
+            "stack: no axis {} for {:?}",
+            dim,
+            xs[0].shape()
+        );
+        let expanded: Vec<Tensor> = xs
+            .iter()
+            .map(|x| {
+                let mut shape = x.shape().to_vec();
+                shape.insert(dim, 1);
+                x.clone().reshape(&shape)
+            })
+            .collect();
+        Tensor::concat(&expanded, dim)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_tensor_grad;
+
+    fn x() -> Tensor {
+        Tensor::newd(
+            (0..24).map(|i| i as f64).collect(),
+            &[2, 3, 4],
+            "x".to_string(),
+        )
+    }
+
+    fn input() -> Vec<(Vec<f64>, Vec<usize>)> {
+        let data = (0..24)
+            .map(|i| ((i * 5) % 7) as f64 - 2.5 + 0.1 * i as f64)
+            .collect();
+        vec![(data, vec![2, 3, 4])]
+    }
+
+    #[test]
+    fn test_reshape() {
+        let x = x();
+        let r = x.clone().reshape(&[4, 6]);
+        assert_eq!(r.shape(), &[4, 6]);
+        assert!(r.shares_storage(&x));
+        assert_eq!(r.get_data(), x.get_data());
+
+        // A permuted tensor is not contiguous, so reshaping it copies.
+        let p = x.clone().transpose(0, 2).reshape(&[24]);
+        assert!(!p.shares_storage(&x));
+        assert_eq!(&p.get_data()[..4], &[0.0, 12.0, 4.0, 16.0]);
+
+        check_tensor_grad(|t| t[0].clone().reshape(&[6, 4]), &input());
+        check_tensor_grad(
+            |t| t[0].clone().permute(&[1, 0, 2]).reshape(&[3, 8]),
+            &input(),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reshape_bad_size() {
+        x().reshape(&[5, 5]);
+    }
+
+    #[test]
+    fn test_permute() {
+        let x = x();
+        let p = x.clone().permute(&[2, 0, 1]);
+        assert_eq!(p.shape(), &[4, 2, 3]);
+        assert!(p.shares_storage(&x));
+        assert_eq!(&p.get_data()[..6], &[0.0, 4.0, 8.0, 12.0, 16.0, 20.0]);
+
+        let t = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3], "t".to_string());
+        assert_eq!(
+            t.transpose(0, 1).get_data(),
+            vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]
+        );
+
+        check_tensor_grad(|t| t[0].clone().permute(&[2, 0, 1]), &input());
+        check_tensor_grad(|t| t[0].clone().transpose(1, 2), &input());
+    }
+
+    #[test]
+    fn test_slice() {
+        let x = x();
+        let s = x.clone().slice(2, 1, 4, 2);
+        assert_eq!(s.shape(), &[2, 3, 2]);
+        assert!(s.shares_storage(&x));
+        assert_eq!(&s.get_data()[..4], &[1.0, 3.0, 5.0, 7.0]);
+
+        let n = x.clone().narrow(1, 1, 2);
+        assert_eq!(n.shape(), &[2, 2, 4]);
+        assert_eq!(n.get_data()[0], 4.0);
+
+        // Views of views.
+        let v = x.clone().transpose(0, 2).slice(0, 0, 4, 3).narrow(1, 2, 1);
+        assert_eq!(v.shape(), &[2, 1, 2]);
+        assert_eq!(v.get_data(), vec![8.0, 20.0, 11.0, 23.0]);
+
+        check_tensor_grad(|t| t[0].clone().slice(2, 1, 4, 2), &input());
+        check_tensor_grad(|t| t[0].clone().narrow(0, 1, 1), &input());
+        check_tensor_grad(
+            |t| {
+                t[0].clone()
+                    .transpose(0, 2)
+                    .slice(0, 0, 4, 3)
+                    .narrow(1, 2, 1)
+            },
+            &input(),
+        );
+    }
+
+    #[test]
+    fn test_index_select() {
+        let s = x().index_select(1, &[2, 0, 2]);
+        assert_eq!(s.shape(), &[2, 3, 4]);
+        assert_eq!(
+            &s.get_data()[..8],
+            &[8.0, 9.0, 10.0, 11.0, 0.0, 1.0, 2.0, 3.0]
+        );
+
+        check_tensor_grad(|t| t[0].clone().index_select(1, &[2, 0, 2]), &input());
+        check_tensor_grad(|t| t[0].clone().index_select(2, &[3]), &input());
+    }
+
+    #[test]
+    fn test_concat() {
+        let a = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0], &[2, 2], "a".to_string());
+        let b = Tensor::newd(vec![5.0, 6.0], &[2, 1], "b".to_string());
+        let c = Tensor::concat(&[a.clone(), b.clone()], 1);
+        assert_eq!(c.shape(), &[2, 3]);
+        assert_eq!(c.get_data(), vec![1.0, 2.0, 5.0, 3.0, 4.0, 6.0]);
+
+        check_tensor_grad(
+            |t| Tensor::concat(&[t[0].clone(), t[1].clone(), t[0].clone()], 1),
+            &[
+                (vec![1.0, -2.0, 3.0, 0.5], vec![2, 2]),
+                (vec![0.3, -0.7], vec![2, 1]),
+            ],
+        );
+        check_tensor_grad(
+            |t| Tensor::concat(&[t[0].clone(), t[1].clone()], 0),
+            &[
+                (vec![1.0, -2.0, 3.0, 0.5], vec![2, 2]),
+                (vec![0.3, -0.7], vec![1, 2]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_stack() {
+        let a = Tensor::newd(vec![1.0, 2.0], &[2], "a".to_string());
+        let b = Tensor::newd(vec![3.0, 4.0], &[2], "b".to_string());
+        let s0 = Tensor::stack(&[a.clone(), b.clone()], 0);
+        assert_eq!(s0.shape(), &[2, 2]);
+        assert_eq!(s0.get_data(), vec![1.0, 2.0, 3.0, 4.0]);
+        let s1 = Tensor::stack(&[a, b], 1);
+        assert_eq!(s1.shape(), &[2, 2]);
+        assert_eq!(s1.get_data(), vec![1.0, 3.0, 2.0, 4.0]);
+
+        check_tensor_grad(
+            |t| Tensor::stack(&[t[0].clone(), t[1].clone()], 1),
+            &[
+                (vec![1.0, -2.0, 3.0], vec![3]),
+                (vec![0.3, -0.7, 0.2], vec![3]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_batch_inputs() {
+        // Moon-style samples batched into one [batch, 2] tensor and pushed
+        // through a dense layer in one matmul.
+        let xs: Vec<[f64; 2]> = vec![[0.5, -0.2], [1.0, 0.3], [-0.7, 0.9]];
+        let rows: Vec<Tensor> = xs
+            .iter()
+            .map(|x| Tensor::newd(x.to_vec(), &[2], "x".to_string()))
+            .collect();
+        let batch = Tensor::stack(&rows, 0);
+        assert_eq!(batch.shape(), &[3, 2]);
+
+        let w = Tensor::newd(vec![1.0, -1.0, 2.0, 0.5], &[2, 2], "w".to_string());
+        let out = batch.matmul(w);
+        assert_eq!(out.shape(), &[3, 2]);
+        let second = out.narrow(0, 1, 1).get_data();
+        assert_eq!(second, vec![1.0 + 0.6, -1.0 + 0.15]);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::tensor::value::Value;
+use log::debug;
+use std::rc::Rc;
+
+fn sin_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+
+    x.set_grad(x.get_grad() + x.get_data().cos() * out.get_grad());
+
+    debug!(
+        "sin_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+fn cos_backward(out: &Value) {
+    let x = out._prev.first().unwrap();
+
+    x.set_grad(x.get_grad() - x.get_data().sin() * out.get_grad());
+
+    debug!(
+        "cos_backwards({}) label {} grad {}",
+        out.get_label(),
+        x.get_label(),
+        x.get_grad()
+    );
+}
+
+impl Value {
+    pub fn sin(self) -> Value {
+        let mut out = Value::new(
+            self.get_data().sin(),
+            vec![self.into_child()],
+            "sin".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(sin_backward);
+        out
+    }
+
+    pub fn cos(self) -> Value {
+        let mut out = Value::new(
+            self.get_data().cos(),
+            vec![self.into_child()],
+            "cos".to_string(),
+            "".to_string(),
+        );
+        out._backward = Rc::new(cos_backward);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::gradcheck::check_unary;
+    #[test]
+    fn test_sin() {
+        check_unary(Value::sin, f64::sin, &[-3.0, -0.5, 0.0, 0.7, 4.0]);
+    }
+
+    #[test]
+    fn test_cos() {
+        check_unary(Value::cos, f64::cos, &[-3.0, -0.5, 0.0, 0.7, 4.0]);
+    }
+}
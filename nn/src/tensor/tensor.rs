@@ -0,0 +1,353 @@
+use crate::ops::kernels;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// N-dimensional array of `f64` that records one graph node per operation.
+///
+/// `data` is a flat storage buffer read through `shape`, `strides` and
+/// `offset`, so several tensors can view the same storage. `grad` is always
+/// contiguous, row-major and has one entry per element of the tensor.
+#[derive(Clone)]
+pub struct Tensor {
+    data: Rc<RefCell<Vec<f64>>>,
+    grad: Rc<RefCell<Vec<f64>>>,
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    offset: usize,
+    pub _prev: Vec<Arc<Tensor>>,
+    _op: Rc<String>,
+    _label: RefCell<String>,
+    pub _backward: Rc<dyn Fn(&Tensor)>,
+}
+
+/// Row-major strides for `shape`.
+pub fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for d in (0..shape.len().saturating_sub(1)).rev() {
+        strides[d] = strides[d + 1] * shape[d + 1];
+    }
+    strides
+}
+
+impl Tensor {
+    pub fn new(
+        data: Vec<f64>,
+        shape: &[usize],
+        child: Vec<Arc<Tensor>>,
+        _op: String,
+        label: String,
+    ) -> Tensor {
+        let numel = shape.iter().product::<usize>();
+        assert_eq!(
+            data.len(),
+            numel,
+            "tensor data has {} elements but shape {:?} needs {}",
+            data.len(),
+            shape,
+            numel
+        );
+        Tensor {
+            data: Rc::new(RefCell::new(data)),
+            grad: Rc::new(RefCell::new(vec![0.0; numel])),
+            shape: shape.to_vec(),
+            strides: contiguous_strides(shape),
+            offset: 0,
+            _prev: child,
+            _op: Rc::new(_op),
+            _label: RefCell::new(label),
+            _backward: Rc::new(|_: &Tensor| {}),
+        }
+    }
+
+    pub fn newd(data: Vec<f64>, shape: &[usize], label: String) -> Tensor {
+        Tensor::new(data, shape, vec![], "".to_string(), label)
+    }
+
+    pub fn zeros(shape: &[usize]) -> Tensor {
+        Tensor::full(shape, 0.0)
+    }
+
+    pub fn ones(shape: &[usize]) -> Tensor {
+        Tensor::full(shape, 1.0)
+    }
+
+    pub fn full(shape: &[usize], v: f64) -> Tensor {
+        let numel = shape.iter().product();
+        Tensor::newd(vec![v; numel], shape, "".to_string())
+    }
+
+    /// 0-dimensional tensor holding `v`.
+    pub fn scalar(v: f64) -> Tensor {
+        Tensor::newd(vec![v], &[], "".to_string())
+    }
+
+    /// Tensor sharing the storage of `self`, read with a different layout.
+    pub(crate) fn view(
+        &self,
+        shape: &[usize],
+        strides: &[usize],
+        offset: usize,
+        _op: String,
+    ) -> Tensor {
+        Tensor {
+            data: self.data.clone(),
+            grad: Rc::new(RefCell::new(vec![0.0; shape.iter().product()])),
+            shape: shape.to_vec(),
+            strides: strides.to_vec(),
+            offset,
+            _prev: vec![self.clone().into_child()],
+            _op: Rc::new(_op),
+            _label: RefCell::new("".to_string()),
+            _backward: Rc::new(|_: &Tensor| {}),
+        }
+    }
+
+    /// `self` as a child of another node, as [`Value::into_child`](crate::tensor::value::Value).
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub(crate) fn into_child(self) -> Arc<Tensor> {
+        Arc::new(self)
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether `self` and `other` read the same storage buffer.
+    pub fn shares_storage(&self, other: &Tensor) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    pub fn numel(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_contiguous(&self) -> bool {
+        self.strides == contiguous_strides(&self.shape)
+    }
+
+    /// Storage position of every element, in row-major order.
+    pub(crate) fn storage_indices(&self) -> Vec<usize> {
+        let numel = self.numel();
+        let mut indices = Vec::with_capacity(numel);
+        let mut index = vec![0; self.ndim()];
+        for _ in 0..numel {
+            indices.push(
+                self.offset
+                    + index
+                        .iter()
+                        .zip(self.strides.iter())
+                        .map(|(i, s)| i * s)
+                        .sum::<usize>(),
+            );
+            for d in (0..self.ndim()).rev() {
+                index[d] += 1;
+                if index[d] < self.shape[d] {
+                    break;
+                }
+                index[d] = 0;
+            }
+        }
+        indices
+    }
+
+    /// Elements in row-major order.
+    pub fn get_data(&self) -> Vec<f64> {
+        let data = (*self.data).borrow();
+        if self.is_contiguous() {
+            data[self.offset..self.offset + self.numel()].to_vec()
+        } else {
+            self.storage_indices().iter().map(|&i| data[i]).collect()
+        }
+    }
+
+    /// Overwrites the elements, given in row-major order. Writes through to
+    /// every tensor sharing the storage.
+    pub fn set_data(&self, d: &[f64]) {
+        assert_eq!(d.len(), self.numel(), "set_data: wrong number of elements");
+        let indices = self.storage_indices();
+        let mut data = (*self.data).borrow_mut();
+        for (&i, &v) in indices.iter().zip(d.iter()) {
+            data[i] = v;
+        }
+    }
+
+    /// Value of a tensor with a single element.
+    pub fn item(&self) -> f64 {
+        assert_eq!(
+            self.numel(),
+            1,
+            "item: tensor has {} elements",
+            self.numel()
+        );
+        self.get_data()[0]
+    }
+
+    pub fn get_grad(&self) -> Vec<f64> {
+        (*self.grad).borrow().clone()
+    }
+
+    pub fn set_grad(&self, d: &[f64]) {
+        let mut grad = (*self.grad).borrow_mut();
+        assert_eq!(d.len(), grad.len(), "set_grad: wrong number of elements");
+        grad.copy_from_slice(d);
+    }
+
+    /// Adds `d` to the gradient, elementwise.
+    pub(crate) fn add_grad(&self, d: &[f64]) {
+        self.update_grad(|grad| kernels::add_backward(d, grad));
+    }
+
+    /// Runs `f` on the gradient buffer, for backward kernels that
+    /// accumulate in place.
+    pub(crate) fn update_grad(&self, f: impl FnOnce(&mut [f64])) {
+        f(&mut (*self.grad).borrow_mut());
+    }
+
+    pub fn get_label(&self) -> String {
+        self._label.borrow().to_string()
+    }
+
+    pub fn set_label(&self, l: &str) {
+        let mut label = self._label.borrow_mut();
+        label.push_str(l);
+    }
+
+    pub fn zero_grad(&self) {
+        (*self.grad).borrow_mut().iter_mut().for_each(|g| *g = 0.0);
+    }
+
+    /// Backpropagates from `self`, seeding its gradient with ones (so a
+    /// non-scalar tensor behaves as if it had been summed).
+    pub fn backward(&self) {
+        self.backward_with(&vec![1.0; self.numel()]);
+    }
+
+    /// Backpropagates `grad`, one entry per element, from `self`.
+    pub(crate) fn backward_with(&self, grad: &[f64]) {
+        // Iterative post-order DFS; a node is identified by its grad buffer,
+        // which clones share and views do not.
+        let mut topo: Vec<&Tensor> = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = vec![(self, false)];
+        while let Some((t, expanded)) = stack.pop() {
+            if expanded {
+                topo.push(t);
+                continue;
+            }
+            if !visited.insert(Rc::as_ptr(&t.grad)) {
+                continue;
+            }
+            stack.push((t, true));
+            for child in t._prev.iter() {
+                if !visited.contains(&Rc::as_ptr(&child.grad)) {
+                    stack.push((child, false));
+                }
+            }
+        }
+
+        self.set_grad(grad);
+        for t in topo.iter().rev() {
+            (t._backward)(t);
+        }
+    }
+}
+
+// Same as for `Value`: tear the graph down without recursing.
+impl Drop for Tensor {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self._prev);
+        while let Some(child) = stack.pop() {
+            if let Some(mut t) = Arc::into_inner(child) {
+                stack.append(&mut t._prev);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Tensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Tensor {{ label: {}, shape: {:?}, data: {:?}, grad: {:?}, op: {}, _prev: {:#?}}}",
+            &self._label.borrow(),
+            self.shape,
+            self.get_data(),
+            (*self.grad).borrow(),
+            &self._op,
+            self._prev,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_new_tensor() {
+        let t = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3], "t".to_string());
+        assert_eq!(t.shape(), &[2, 3]);
+        assert_eq!(t.strides(), &[3, 1]);
+        assert_eq!(t.numel(), 6);
+        assert_eq!(t.ndim(), 2);
+        assert!(t.is_contiguous());
+        assert_eq!(t.get_grad(), vec![0.0; 6]);
+        assert_eq!(t.get_label(), "t");
+
+        let s = Tensor::scalar(2.5);
+        assert_eq!(s.shape(), &[] as &[usize]);
+        assert_eq!(s.item(), 2.5);
+
+        assert_eq!(Tensor::ones(&[2, 2]).get_data(), vec![1.0; 4]);
+        assert_eq!(contiguous_strides(&[2, 3, 4]), vec![12, 4, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_tensor_bad_shape() {
+        Tensor::newd(vec![1.0, 2.0, 3.0], &[2, 2], "t".to_string());
+    }
+
+    #[test]
+    fn test_strided_view() {
+        let t = Tensor::newd(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3], "t".to_string());
+        // Transposed layout over the same storage.
+        let v = t.view(&[3, 2], &[1, 3], 0, "view".to_string());
+        assert!(!v.is_contiguous());
+        assert!(v.shares_storage(&t));
+        assert_eq!(v.get_data(), vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+
+        v.set_data(&[10.0, 40.0, 20.0, 50.0, 30.0, 60.0]);
+        assert_eq!(t.get_data(), vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0]);
+
+        // Second column, read from an offset.
+        let c = t.view(&[2], &[3], 1, "view".to_string());
+        assert_eq!(c.get_data(), vec![20.0, 50.0]);
+    }
+
+    #[test]
+    fn test_drop_deep_graph() {
+        let x = Tensor::ones(&[1]);
+        let mut t = Tensor::zeros(&[1]);
+        for _ in 0..200_000 {
+            t = t + x.clone();
+        }
+        assert_eq!(t.item(), 200_000.0);
+        drop(t);
+    }
+}
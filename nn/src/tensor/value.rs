@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fmt;
 use std::iter::Sum;
 use std::rc::Rc;
@@ -11,8 +12,7 @@ pub struct Value {
     pub _prev: Vec<Arc<Value>>,
     _op: Rc<String>,
     _label: RefCell<String>,
-    // pub _backward: Arc<Box<dyn Fn(&Value)>>,
-    pub _backward: Rc<fn(&Value)>,
+    pub _backward: Rc<dyn Fn(&Value)>,
 }
 
 impl Default for Value {
@@ -41,12 +41,14 @@ impl Value {
     }
 
     pub fn newd(data: f64, label: String) -> Value {
-        Value {
-            data: Rc::new(RefCell::new(data)),
-            // _prev: child,
-            _label: RefCell::new(label),
-            ..Default::default()
-        }
+        Value::new(data, vec![], "".to_string(), label)
+    }
+
+    /// `self` as a child of another node. Graphs never cross threads; the
+    /// `Arc` only lets several parents share a child.
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub(crate) fn into_child(self) -> Arc<Value> {
+        Arc::new(self)
     }
 
     pub fn vec(inv: &[f64]) -> Vec<Value> {
@@ -88,18 +90,27 @@ impl Value {
     }
 
     pub fn backward(&self) {
-        let mut topo = vec![];
-        let mut visited = vec![];
-        fn build_topo<'a>(v: &'a Value, topo: &mut Vec<&'a Value>, visited: &mut Vec<&'a Value>) {
-            if !visited.contains(&v) {
-                visited.push(v);
-                for child in v._prev.iter() {
-                    build_topo(child, topo, visited);
-                }
+        // Iterative post-order DFS. A node is identified by its grad cell,
+        // which clones share, so structurally equal but distinct nodes are
+        // still visited separately.
+        let mut topo: Vec<&Value> = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = vec![(self, false)];
+        while let Some((v, expanded)) = stack.pop() {
+            if expanded {
                 topo.push(v);
+                continue;
+            }
+            if !visited.insert(Rc::as_ptr(&v.grad)) {
+                continue;
+            }
+            stack.push((v, true));
+            for child in v._prev.iter() {
+                if !visited.contains(&Rc::as_ptr(&child.grad)) {
+                    stack.push((child, false));
+                }
             }
         }
-        build_topo(self, &mut topo, &mut visited);
 
         *(*self.grad).borrow_mut() = 1.0;
         for v in topo.iter().rev() {
@@ -108,6 +119,20 @@ impl Value {
     }
 }
 
+// Dropping a node recursively drops its `_prev` chain, which overflows the
+// stack for deep graphs (e.g. a loss summed over many terms). Instead, move
+// the children of every uniquely owned node onto an explicit stack.
+impl Drop for Value {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self._prev);
+        while let Some(child) = stack.pop() {
+            if let Some(mut v) = Arc::into_inner(child) {
+                stack.append(&mut v._prev);
+            }
+        }
+    }
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         *(*self.data).borrow() == *(*other.data).borrow()
@@ -136,10 +161,7 @@ impl Sum for Value {
     where
         I: Iterator<Item = Value>,
     {
-        iter.fold(
-            Value::new(0.0, vec![], "".to_string(), "".to_string()),
-            |a, b| a + b,
-        )
+        Value::sum_all(&iter.collect::<Vec<_>>())
     }
 }
 
@@ -147,6 +169,46 @@ impl Sum for Value {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_drop_deep_graph() {
+        let x = Value::newd(1.0, "x".to_string());
+        let loss = (0..2_000_000).fold(Value::newd(0.0, "".to_string()), |a, _| a + x.clone());
+        assert_eq!(loss.get_data(), 2_000_000.0);
+        drop(loss);
+        assert_eq!(x.get_data(), 1.0);
+    }
+
+    #[test]
+    fn test_drop_keeps_shared_nodes() {
+        let a = Value::newd(2.0, "a".to_string());
+        let b = a.clone() * 3.0;
+        let c = b.clone() + 1.0;
+        drop(b);
+        c.backward();
+        assert_eq!(c.get_data(), 7.0);
+        assert_eq!(a.get_grad(), 3.0);
+    }
+
+    #[test]
+    fn test_backward_equal_nodes() {
+        // Two separately built, structurally identical products must both
+        // propagate their gradient.
+        let a = Value::newd(2.0, "a".to_string());
+        let b = Value::newd(3.0, "b".to_string());
+        let c = a.clone() * b.clone() + a.clone() * b.clone();
+        c.backward();
+        assert_eq!(a.get_grad(), 6.0);
+        assert_eq!(b.get_grad(), 4.0);
+    }
+
+    #[test]
+    fn test_backward_deep_graph() {
+        let x = Value::newd(1.0, "x".to_string());
+        let loss = (0..200_000).fold(Value::newd(0.0, "".to_string()), |a, _| a + x.clone());
+        loss.backward();
+        assert_eq!(x.get_grad(), 200_000.0);
+    }
+
     #[test]
     fn test_clone_values() {
         let a = Value::new(-2.0, vec![], "".to_string(), "a".to_string());
@@ -180,13 +242,13 @@ mod test {
         println!(" d {:#?}", *(*d.data).borrow());
         assert_eq!(d.get_data(), 4.0);
         let f = Value::new(2.0, vec![], "".to_string(), "f".to_string());
-        let L = d.clone() * f.clone();
-        assert_eq!(L.get_data(), 8.0);
-        println!(" L {:#?}", *(*L.data).borrow());
-        L.set_label("L");
-        // println!(" {:#?}", L);
-        L.backward();
-        println!(" {:#?}", L);
+        let l = d.clone() * f.clone();
+        assert_eq!(l.get_data(), 8.0);
+        println!(" l {:#?}", *(*l.data).borrow());
+        l.set_label("l");
+        // println!(" {:#?}", l);
+        l.backward();
+        println!(" {:#?}", l);
 
         assert_eq!(a.get_grad(), -6.0);
         assert_eq!(b.get_grad(), 4.0);
@@ -0,0 +1,178 @@
+//! Everything needed to resume a [`Trainer`](crate::train::train::Trainer)
+//! run exactly.
+//!
+//! # File format
+//!
+//! Everything is little-endian.
+//!
+//! | size | field                                                 |
+//! |------|-------------------------------------------------------|
+//! | 4    | magic, the bytes `NNCK`                               |
+//! | 4    | format version, `u32`, currently 1                    |
+//! | 8    | completed epochs, `u64`                               |
+//! | 8    | completed steps, `u64`                                |
+//! | 8    | samples of the current epoch already seen, `u64`      |
+//! | 32   | RNG seed                                              |
+//! | 8    | RNG stream, `u64`                                     |
+//! | 16   | RNG word position, `u128`                             |
+//!
+//! followed by the model, optimizer and scheduler state dicts, each as a
+//! `u64` byte length and then the dict in the
+//! [state dict format](crate::mlp::state_dict). Nothing may follow.
+
+use crate::error::NnError;
+use crate::mlp::state_dict::{Reader, StateDict};
+use std::fs;
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"NNCK";
+pub const VERSION: u32 = 1;
+
+/// Position of the ChaCha12 generator shuffling the samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// Model weights by parameter path.
+    pub model: StateDict,
+    pub optimizer: StateDict,
+    pub scheduler: StateDict,
+    pub epoch: u64,
+    pub steps: u64,
+    /// Position in the current epoch's permutation.
+    pub cursor: u64,
+    /// The RNG before drawing the current epoch's permutation, or at an
+    /// epoch boundary, before drawing the next one.
+    pub rng: RngState,
+}
+
+impl Checkpoint {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&self.epoch.to_le_bytes());
+        out.extend_from_slice(&self.steps.to_le_bytes());
+        out.extend_from_slice(&self.cursor.to_le_bytes());
+        out.extend_from_slice(&self.rng.seed);
+        out.extend_from_slice(&self.rng.stream.to_le_bytes());
+        out.extend_from_slice(&self.rng.word_pos.to_le_bytes());
+        for state in [&self.model, &self.optimizer, &self.scheduler] {
+            let bytes = state.to_bytes();
+            out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            out.extend_from_slice(&bytes);
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Checkpoint, NnError> {
+        let mut r = Reader::new(bytes, "checkpoint");
+        if r.take(4)? != MAGIC {
+            return Err(NnError::Format("not a checkpoint, bad magic".to_string()));
+        }
+        let version = u32::from_le_bytes(r.array()?);
+        if version != VERSION {
+            return Err(NnError::UnsupportedVersion(version));
+        }
+        let epoch = u64::from_le_bytes(r.array()?);
+        let steps = u64::from_le_bytes(r.array()?);
+        let cursor = u64::from_le_bytes(r.array()?);
+        let rng = RngState {
+            seed: r.array()?,
+            stream: u64::from_le_bytes(r.array()?),
+            word_pos: u128::from_le_bytes(r.array()?),
+        };
+        let checkpoint = Checkpoint {
+            model: StateDict::from_bytes(r.blob()?)?,
+            optimizer: StateDict::from_bytes(r.blob()?)?,
+            scheduler: StateDict::from_bytes(r.blob()?)?,
+            epoch,
+            steps,
+            cursor,
+            rng,
+        };
+        r.finish()?;
+        Ok(checkpoint)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), NnError> {
+        fs::write(path, self.to_bytes()).map_err(|e| NnError::Io(e.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Checkpoint, NnError> {
+        Checkpoint::from_bytes(&fs::read(path).map_err(|e| NnError::Io(e.to_string()))?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checkpoint() -> Checkpoint {
+        let mut model = StateDict::new();
+        model.insert("layers.0.neurons.0.w0".to_string(), 0.25);
+        let mut optimizer = StateDict::new();
+        optimizer.insert("t".to_string(), 3.0);
+        let mut scheduler = StateDict::new();
+        scheduler.insert("steps".to_string(), 3.0);
+        Checkpoint {
+            model,
+            optimizer,
+            scheduler,
+            epoch: 1,
+            steps: 3,
+            cursor: 8,
+            rng: RngState {
+                seed: [7; 32],
+                stream: 2,
+                word_pos: u128::MAX - 5,
+            },
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let ck = checkpoint();
+        assert_eq!(Checkpoint::from_bytes(&ck.to_bytes()).unwrap(), ck);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let bytes = checkpoint().to_bytes();
+        let truncated = NnError::Format("truncated checkpoint".to_string());
+        assert_eq!(
+            Checkpoint::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            truncated
+        );
+        // A state dict length running past the end, right after the header.
+        let mut huge = bytes.clone();
+        huge[88..96].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(Checkpoint::from_bytes(&huge).unwrap_err(), truncated);
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Checkpoint::from_bytes(&trailing).unwrap_err(),
+            NnError::Format("trailing bytes in checkpoint".to_string())
+        );
+
+        let mut magic = bytes.clone();
+        magic[..4].copy_from_slice(b"NNSD");
+        assert!(matches!(
+            Checkpoint::from_bytes(&magic),
+            Err(NnError::Format(_))
+        ));
+
+        let mut version = bytes;
+        version[4..8].copy_from_slice(&9u32.to_le_bytes());
+        assert_eq!(
+            Checkpoint::from_bytes(&version).unwrap_err(),
+            NnError::UnsupportedVersion(9)
+        );
+    }
+}
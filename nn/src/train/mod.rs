@@ -0,0 +1,5 @@
+pub mod checkpoint;
+pub mod optim;
+pub mod scheduler;
+#[allow(clippy::module_inception)]
+pub mod train;
@@ -0,0 +1,258 @@
+use crate::error::NnError;
+use crate::mlp::state_dict::StateDict;
+use crate::tensor::value::Value;
+use std::collections::HashMap;
+
+/// Updates parameters from their gradients.
+///
+/// Parameters come with their
+/// [`named_parameters`](crate::mlp::module::Module::named_parameters) paths,
+/// which key any per-parameter state so that it survives a checkpoint and
+/// follows the right parameter when some are frozen.
+pub trait Optimizer {
+    fn step(&mut self, params: &[(String, &Value)], lr: f64);
+
+    fn state_dict(&self) -> StateDict;
+
+    fn load_state_dict(&mut self, state: &StateDict) -> Result<(), NnError>;
+}
+
+/// Entries of `state` named `prefix.<path>`, keyed by path. Anything else is
+/// unexpected, except for the names in `scalars`.
+fn buffers(
+    state: &StateDict,
+    prefixes: &[&str],
+    scalars: &[&str],
+) -> Result<Vec<HashMap<String, f64>>, NnError> {
+    let mut out = vec![HashMap::new(); prefixes.len()];
+    let mut unexpected = vec![];
+    for (name, value) in state.iter() {
+        let found = prefixes.iter().position(|p| {
+            name.strip_prefix(p)
+                .is_some_and(|rest| rest.starts_with('.'))
+        });
+        match found {
+            Some(i) => {
+                out[i].insert(name[prefixes[i].len() + 1..].to_string(), value);
+            }
+            None if scalars.contains(&name) => {}
+            None => unexpected.push(name.to_string()),
+        }
+    }
+    if !unexpected.is_empty() {
+        return Err(NnError::StateDictMismatch {
+            missing: vec![],
+            unexpected,
+        });
+    }
+    Ok(out)
+}
+
+fn insert_buffer(state: &mut StateDict, prefix: &str, buffer: &HashMap<String, f64>) {
+    let mut names: Vec<&String> = buffer.keys().collect();
+    names.sort();
+    for name in names {
+        state.insert(format!("{}.{}", prefix, name), buffer[name]);
+    }
+}
+
+/// Stochastic gradient descent with optional momentum and L2 weight decay:
+/// `v = momentum * v + g + weight_decay * p`, `p -= lr * v`.
+#[derive(Debug, Clone, Default)]
+pub struct Sgd {
+    momentum: f64,
+    weight_decay: f64,
+    velocity: HashMap<String, f64>,
+}
+
+impl Sgd {
+    pub fn new() -> Sgd {
+        Sgd::default()
+    }
+
+    pub fn momentum(mut self, momentum: f64) -> Sgd {
+        self.momentum = momentum;
+        self
+    }
+
+    pub fn weight_decay(mut self, weight_decay: f64) -> Sgd {
+        self.weight_decay = weight_decay;
+        self
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, params: &[(String, &Value)], lr: f64) {
+        for (name, p) in params {
+            let g = p.get_grad() + self.weight_decay * p.get_data();
+            let d = if self.momentum == 0.0 {
+                g
+            } else {
+                let v = self.velocity.entry(name.clone()).or_insert(0.0);
+                *v = self.momentum * *v + g;
+                *v
+            };
+            p.set_data(p.get_data() - lr * d);
+        }
+    }
+
+    /// `velocity.<path>` per parameter.
+    fn state_dict(&self) -> StateDict {
+        let mut state = StateDict::new();
+        insert_buffer(&mut state, "velocity", &self.velocity);
+        state
+    }
+
+    fn load_state_dict(&mut self, state: &StateDict) -> Result<(), NnError> {
+        self.velocity = buffers(state, &["velocity"], &[])?.remove(0);
+        Ok(())
+    }
+}
+
+/// Adam with bias correction.
+#[derive(Debug, Clone)]
+pub struct Adam {
+    beta1: f64,
+    beta2: f64,
+    eps: f64,
+    t: u64,
+    m: HashMap<String, f64>,
+    v: HashMap<String, f64>,
+}
+
+impl Default for Adam {
+    fn default() -> Adam {
+        Adam::new(0.9, 0.999, 1e-8)
+    }
+}
+
+impl Adam {
+    pub fn new(beta1: f64, beta2: f64, eps: f64) -> Adam {
+        Adam {
+            beta1,
+            beta2,
+            eps,
+            t: 0,
+            m: HashMap::new(),
+            v: HashMap::new(),
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, params: &[(String, &Value)], lr: f64) {
+        self.t += 1;
+        let c1 = 1.0 - self.beta1.powi(self.t as i32);
+        let c2 = 1.0 - self.beta2.powi(self.t as i32);
+        for (name, p) in params {
+            let g = p.get_grad();
+            let m = self.m.entry(name.clone()).or_insert(0.0);
+            *m = self.beta1 * *m + (1.0 - self.beta1) * g;
+            let v = self.v.entry(name.clone()).or_insert(0.0);
+            *v = self.beta2 * *v + (1.0 - self.beta2) * g * g;
+            let update = (*m / c1) / ((*v / c2).sqrt() + self.eps);
+            p.set_data(p.get_data() - lr * update);
+        }
+    }
+
+    /// `t`, then `m.<path>` and `v.<path>` per parameter.
+    fn state_dict(&self) -> StateDict {
+        let mut state = StateDict::new();
+        state.insert("t".to_string(), self.t as f64);
+        insert_buffer(&mut state, "m", &self.m);
+        insert_buffer(&mut state, "v", &self.v);
+        state
+    }
+
+    fn load_state_dict(&mut self, state: &StateDict) -> Result<(), NnError> {
+        let t = state.get("t").ok_or_else(|| NnError::StateDictMismatch {
+            missing: vec!["t".to_string()],
+            unexpected: vec![],
+        })?;
+        let mut b = buffers(state, &["m", "v"], &["t"])?;
+        self.t = t as u64;
+        self.v = b.pop().unwrap();
+        self.m = b.pop().unwrap();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn param(data: f64, grad: f64) -> Value {
+        let p = Value::newd(data, "p".to_string());
+        p.set_grad(grad);
+        p
+    }
+
+    #[test]
+    fn test_sgd() {
+        let p = param(1.0, 0.5);
+        let mut sgd = Sgd::new();
+        sgd.step(&[("p".to_string(), &p)], 0.1);
+        assert_eq!(p.get_data(), 1.0 - 0.05);
+        assert!(sgd.state_dict().is_empty());
+
+        let p = param(1.0, 0.5);
+        let mut sgd = Sgd::new().momentum(0.9).weight_decay(0.1);
+        let params = [("p".to_string(), &p)];
+        sgd.step(&params, 0.1);
+        // v = 0.5 + 0.1 * 1.0
+        assert!((p.get_data() - (1.0 - 0.1 * 0.6)).abs() < 1e-15);
+        let p1 = p.get_data();
+        sgd.step(&params, 0.1);
+        let v = 0.9 * 0.6 + 0.5 + 0.1 * p1;
+        assert!((p.get_data() - (p1 - 0.1 * v)).abs() < 1e-15);
+        assert!((sgd.state_dict().get("velocity.p").unwrap() - v).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_adam() {
+        // The first bias-corrected step moves by lr * sign(g).
+        let (a, b) = (param(1.0, 0.5), param(-1.0, -2.0));
+        let mut adam = Adam::default();
+        adam.step(&[("a".to_string(), &a), ("b".to_string(), &b)], 0.01);
+        assert!((a.get_data() - 0.99).abs() < 1e-9);
+        assert!((b.get_data() + 0.99).abs() < 1e-9);
+        let state = adam.state_dict();
+        assert_eq!(state.get("t"), Some(1.0));
+        assert!((state.get("m.a").unwrap() - 0.05).abs() < 1e-15);
+        assert_eq!(
+            state.keys().collect::<Vec<_>>(),
+            ["t", "m.a", "m.b", "v.a", "v.b"]
+        );
+    }
+
+    #[test]
+    fn test_load_state_dict() {
+        let params = |p: &Value| vec![("layers.0.w".to_string(), p.clone())];
+        let run = |adam: &mut Adam, p: &Value| {
+            let named = params(p);
+            let refs: Vec<(String, &Value)> = named.iter().map(|(n, p)| (n.clone(), p)).collect();
+            adam.step(&refs, 0.1);
+        };
+        let (a, b) = (param(1.0, 0.3), param(1.0, 0.3));
+        let mut first = Adam::default();
+        run(&mut first, &a);
+        let mut second = Adam::default();
+        second.load_state_dict(&first.state_dict()).unwrap();
+        b.set_data(a.get_data());
+        run(&mut first, &a);
+        run(&mut second, &b);
+        assert_eq!(a.get_data().to_bits(), b.get_data().to_bits());
+
+        let mut state = first.state_dict();
+        state.insert("velocity.x".to_string(), 0.0);
+        assert_eq!(
+            Adam::default().load_state_dict(&state).unwrap_err(),
+            NnError::StateDictMismatch {
+                missing: vec![],
+                unexpected: vec!["velocity.x".to_string()]
+            }
+        );
+        assert!(Sgd::new().load_state_dict(&first.state_dict()).is_err());
+        assert!(Adam::default().load_state_dict(&StateDict::new()).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::error::NnError;
+use crate::mlp::state_dict::StateDict;
+
+/// Learning rate as a function of the number of steps taken.
+pub trait LrScheduler {
+    /// The learning rate for the next step.
+    fn lr(&self) -> f64;
+
+    /// Advances by one optimizer step.
+    fn step(&mut self);
+
+    fn state_dict(&self) -> StateDict;
+
+    fn load_state_dict(&mut self, state: &StateDict) -> Result<(), NnError>;
+}
+
+/// The step count, the only state of the schedulers below.
+fn steps(state: &StateDict) -> Result<u64, NnError> {
+    let unexpected: Vec<String> = state
+        .keys()
+        .filter(|k| *k != "steps")
+        .map(str::to_string)
+        .collect();
+    match state.get("steps") {
+        Some(steps) if unexpected.is_empty() => Ok(steps as u64),
+        steps => Err(NnError::StateDictMismatch {
+            missing: match steps {
+                Some(_) => vec![],
+                None => vec!["steps".to_string()],
+            },
+            unexpected,
+        }),
+    }
+}
+
+fn steps_state(steps: u64) -> StateDict {
+    let mut state = StateDict::new();
+    state.insert("steps".to_string(), steps as f64);
+    state
+}
+
+/// `lr * gamma^(steps / step_size)`.
+#[derive(Debug, Clone)]
+pub struct StepLr {
+    lr: f64,
+    gamma: f64,
+    step_size: u64,
+    steps: u64,
+}
+
+impl StepLr {
+    pub fn new(lr: f64, step_size: u64, gamma: f64) -> StepLr {
+        assert!(step_size > 0, "StepLr: step_size must be positive");
+        StepLr {
+            lr,
+            gamma,
+            step_size,
+            steps: 0,
+        }
+    }
+}
+
+impl LrScheduler for StepLr {
+    fn lr(&self) -> f64 {
+        self.lr * self.gamma.powi((self.steps / self.step_size) as i32)
+    }
+
+    fn step(&mut self) {
+        self.steps += 1;
+    }
+
+    fn state_dict(&self) -> StateDict {
+        steps_state(self.steps)
+    }
+
+    fn load_state_dict(&mut self, state: &StateDict) -> Result<(), NnError> {
+        self.steps = steps(state)?;
+        Ok(())
+    }
+}
+
+/// Linear from `start` to `end` over `total` steps, then `end`. The sample
+/// app's `1.0 - 0.9 * i / 100` is `LinearLr::new(1.0, 0.1, 100)`.
+#[derive(Debug, Clone)]
+pub struct LinearLr {
+    start: f64,
+    end: f64,
+    total: u64,
+    steps: u64,
+}
+
+impl LinearLr {
+    pub fn new(start: f64, end: f64, total: u64) -> LinearLr {
+        assert!(total > 0, "LinearLr: total must be positive");
+        LinearLr {
+            start,
+            end,
+            total,
+            steps: 0,
+        }
+    }
+}
+
+impl LrScheduler for LinearLr {
+    fn lr(&self) -> f64 {
+        let t = self.steps.min(self.total) as f64 / self.total as f64;
+        self.start - (self.start - self.end) * t
+    }
+
+    fn step(&mut self) {
+        self.steps += 1;
+    }
+
+    fn state_dict(&self) -> StateDict {
+        steps_state(self.steps)
+    }
+
+    fn load_state_dict(&mut self, state: &StateDict) -> Result<(), NnError> {
+        self.steps = steps(state)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_step_lr() {
+        let mut s = StepLr::new(1.0, 2, 0.5);
+        let lrs: Vec<f64> = (0..5)
+            .map(|_| {
+                let lr = s.lr();
+                s.step();
+                lr
+            })
+            .collect();
+        assert_eq!(lrs, [1.0, 1.0, 0.5, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn test_linear_lr() {
+        let mut s = LinearLr::new(1.0, 0.1, 100);
+        for i in 0..120 {
+            let expected = if i < 100 {
+                1.0 - (0.9 * i as f64) / 100.0
+            } else {
+                0.1
+            };
+            assert!((s.lr() - expected).abs() < 1e-12, "{}", i);
+            s.step();
+        }
+    }
+
+    #[test]
+    fn test_state_dict() {
+        let mut s = StepLr::new(1.0, 3, 0.1);
+        (0..7).for_each(|_| s.step());
+        let mut resumed = StepLr::new(1.0, 3, 0.1);
+        resumed.load_state_dict(&s.state_dict()).unwrap();
+        assert_eq!(resumed.lr(), s.lr());
+
+        let mut bad = s.state_dict();
+        bad.insert("t".to_string(), 1.0);
+        assert_eq!(
+            resumed.load_state_dict(&bad).unwrap_err(),
+            NnError::StateDictMismatch {
+                missing: vec![],
+                unexpected: vec!["t".to_string()]
+            }
+        );
+        assert!(resumed.load_state_dict(&StateDict::new()).is_err());
+    }
+}
@@ -1,3 +1,398 @@
-struct Trainer {
+use crate::error::NnError;
+use crate::mlp::module::Module;
+use crate::tensor::value::Value;
+use crate::train::checkpoint::{Checkpoint, RngState};
+use crate::train::optim::Optimizer;
+use crate::train::scheduler::LrScheduler;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+/// A minibatch training run: the optimizer and learning rate schedule, the
+/// step and epoch counters, and the RNG shuffling the samples. Everything it
+/// holds goes into a [`Checkpoint`], so a run can stop and resume exactly.
+pub struct Trainer<O: Optimizer, S: LrScheduler> {
+    optimizer: O,
+    scheduler: S,
+    len: usize,
     batch_size: usize,
+    frozen: Vec<String>,
+    epoch: u64,
+    steps: u64,
+    rng: ChaCha12Rng,
+    /// The RNG as it was before shuffling `order`, from which a checkpoint
+    /// taken mid-epoch redraws it.
+    shuffled_from: RngState,
+    /// This epoch's permutation of the samples, walked from `cursor`.
+    order: Vec<usize>,
+    cursor: usize,
+}
+
+fn rng_state(rng: &ChaCha12Rng) -> RngState {
+    RngState {
+        seed: rng.get_seed(),
+        stream: rng.get_stream(),
+        word_pos: rng.get_word_pos(),
+    }
+}
+
+fn rng_from(state: &RngState) -> ChaCha12Rng {
+    let mut rng = ChaCha12Rng::from_seed(state.seed);
+    rng.set_stream(state.stream);
+    rng.set_word_pos(state.word_pos);
+    rng
+}
+
+fn permutation(len: usize, rng: &mut ChaCha12Rng) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    order.shuffle(rng);
+    order
+}
+
+impl<O: Optimizer, S: LrScheduler> Trainer<O, S> {
+    /// Trains on a dataset of `len` samples, `batch_size` of them per step.
+    /// Every epoch walks a fresh permutation of the samples drawn by an RNG
+    /// seeded with `seed`, so each sample is seen once per epoch.
+    pub fn new(optimizer: O, scheduler: S, len: usize, batch_size: usize, seed: u64) -> Self {
+        assert!(
+            batch_size > 0 && batch_size <= len,
+            "Trainer: batch_size {} out of 1..={}",
+            batch_size,
+            len
+        );
+        let rng = ChaCha12Rng::seed_from_u64(seed);
+        Trainer {
+            optimizer,
+            scheduler,
+            len,
+            batch_size,
+            frozen: vec![],
+            epoch: 0,
+            steps: 0,
+            shuffled_from: rng_state(&rng),
+            rng,
+            order: vec![],
+            cursor: 0,
+        }
+    }
+
+    /// Leaves the parameters under the `frozen` paths untouched, as
+    /// [`Module::trainable_parameters`] selects them.
+    pub fn freeze(mut self, frozen: &[&str]) -> Self {
+        self.frozen = frozen.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    pub fn optimizer(&self) -> &O {
+        &self.optimizer
+    }
+
+    pub fn scheduler(&self) -> &S {
+        &self.scheduler
+    }
+
+    /// Completed epochs: one per `ceil(len / batch_size)` steps.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Completed steps.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// One optimizer step: takes the next batch of sample indices from this
+    /// epoch's permutation, builds the loss on it with `loss`,
+    /// backpropagates and updates every parameter of `model` that is not
+    /// frozen. The last batch of an epoch holds what is left, so it may be
+    /// smaller. Returns the loss.
+    pub fn step(&mut self, model: &dyn Module, loss: impl FnOnce(&[usize]) -> Value) -> f64 {
+        if self.cursor == 0 {
+            self.shuffled_from = rng_state(&self.rng);
+            self.order = permutation(self.len, &mut self.rng);
+        }
+        let end = (self.cursor + self.batch_size).min(self.len);
+        let loss = loss(&self.order[self.cursor..end]);
+        model.zero_grad();
+        loss.backward();
+        let frozen: Vec<&str> = self.frozen.iter().map(String::as_str).collect();
+        self.optimizer.step(
+            &model.named_trainable_parameters(&frozen),
+            self.scheduler.lr(),
+        );
+        self.scheduler.step();
+        self.steps += 1;
+        self.cursor = end;
+        if self.cursor == self.len {
+            self.cursor = 0;
+            self.epoch += 1;
+        }
+        loss.get_data()
+    }
+
+    /// Snapshot of the run and of `model`'s weights.
+    pub fn checkpoint(&self, model: &dyn Module) -> Checkpoint {
+        Checkpoint {
+            model: model.state_dict(),
+            optimizer: self.optimizer.state_dict(),
+            scheduler: self.scheduler.state_dict(),
+            epoch: self.epoch,
+            steps: self.steps,
+            cursor: self.cursor as u64,
+            rng: if self.cursor == 0 {
+                rng_state(&self.rng)
+            } else {
+                self.shuffled_from
+            },
+        }
+    }
+
+    /// Restores the run and `model` from `checkpoint`. Either everything is
+    /// restored or, on error, nothing is.
+    pub fn load_checkpoint(
+        &mut self,
+        model: &dyn Module,
+        checkpoint: &Checkpoint,
+    ) -> Result<(), NnError>
+    where
+        O: Clone,
+        S: Clone,
+    {
+        let cursor = usize::try_from(checkpoint.cursor)
+            .ok()
+            .filter(|&c| c < self.len)
+            .ok_or_else(|| {
+                NnError::Format(format!(
+                    "checkpoint position {} outside a dataset of {}",
+                    checkpoint.cursor, self.len
+                ))
+            })?;
+        let mut optimizer = self.optimizer.clone();
+        optimizer.load_state_dict(&checkpoint.optimizer)?;
+        let mut scheduler = self.scheduler.clone();
+        scheduler.load_state_dict(&checkpoint.scheduler)?;
+        model.load_state_dict(&checkpoint.model)?;
+
+        self.optimizer = optimizer;
+        self.scheduler = scheduler;
+        self.epoch = checkpoint.epoch;
+        self.steps = checkpoint.steps;
+        self.cursor = cursor;
+        self.shuffled_from = checkpoint.rng;
+        self.rng = rng_from(&checkpoint.rng);
+        // Mid-epoch, redraw the permutation being walked.
+        if cursor > 0 {
+            self.order = permutation(self.len, &mut self.rng);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mlp::mlp::MLP;
+    use crate::ops::activation::Activation;
+    use crate::train::optim::{Adam, Sgd};
+    use crate::train::scheduler::{LinearLr, StepLr};
+
+    /// Two interleaved half moons, labels ±1.
+    fn moons() -> (Vec<Vec<f64>>, Vec<f64>) {
+        (0..40)
+            .map(|i| {
+                let t = std::f64::consts::PI * (i / 2) as f64 / 19.0;
+                if i % 2 == 0 {
+                    (vec![t.cos(), t.sin()], 1.0)
+                } else {
+                    (vec![1.0 - t.cos(), 0.5 - t.sin()], -1.0)
+                }
+            })
+            .unzip()
+    }
+
+    fn model(seed: u64) -> MLP {
+        MLP::builder(2)
+            .seed(seed)
+            .dense(8, Activation::Tanh)
+            .dense(8, Activation::Relu)
+            .dense(1, Activation::Identity)
+            .build()
+    }
+
+    /// The sample app's hinge loss over a batch.
+    fn hinge(model: &MLP, xs: &[Vec<f64>], ys: &[f64], batch: &[usize]) -> Value {
+        let inputs: Vec<Vec<f64>> = batch.iter().map(|&i| xs[i].clone()).collect();
+        let losses: Value = model
+            .forward_batch(&inputs)
+            .unwrap()
+            .into_iter()
+            .zip(batch)
+            .map(|(mut y, &i)| (1.0 + y.remove(0) * -ys[i]).relu())
+            .sum();
+        losses / batch.len() as f64
+    }
+
+    fn bits(model: &MLP) -> Vec<u64> {
+        model
+            .parameters()
+            .iter()
+            .map(|p| p.get_data().to_bits())
+            .collect()
+    }
+
+    #[test]
+    fn test_trainer() {
+        let (xs, ys) = moons();
+        let mlp = model(0);
+        let mut trainer = Trainer::new(
+            Sgd::new().momentum(0.9),
+            LinearLr::new(0.1, 0.01, 100),
+            40,
+            8,
+            0,
+        );
+        let losses: Vec<f64> = (0..100)
+            .map(|_| trainer.step(&mlp, |batch| hinge(&mlp, &xs, &ys, batch)))
+            .collect();
+        assert_eq!(trainer.steps(), 100);
+        assert_eq!(trainer.epoch(), 20);
+        let early: f64 = losses[..10].iter().sum();
+        let late: f64 = losses[90..].iter().sum();
+        assert!(late < 0.5 * early, "{} {}", early, late);
+    }
+
+    #[test]
+    fn test_batches() {
+        // Each epoch walks one permutation: every index once, the last batch
+        // holding the remainder, and the seed fixes the order.
+        let draw = |seed| {
+            let mlp = model(0);
+            let mut trainer = Trainer::new(Sgd::new(), StepLr::new(0.0, 1, 1.0), 10, 4, seed);
+            let mut seen: Vec<Vec<usize>> = vec![];
+            for _ in 0..6 {
+                trainer.step(&mlp, |batch| {
+                    seen.push(batch.to_vec());
+                    mlp.forward(&[0.0, 0.0]).unwrap().remove(0)
+                });
+            }
+            // ceil(10 / 4) = 3 steps per epoch.
+            assert_eq!(trainer.epoch(), 2);
+            let sizes: Vec<usize> = seen.iter().map(Vec::len).collect();
+            assert_eq!(sizes, [4, 4, 2, 4, 4, 2]);
+            let epochs: Vec<Vec<usize>> = seen.chunks(3).map(|e| e.concat()).collect();
+            for epoch in epochs.iter() {
+                let mut sorted = epoch.clone();
+                sorted.sort();
+                assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+            }
+            assert_ne!(epochs[0], epochs[1]);
+            seen
+        };
+        assert_eq!(draw(3), draw(3));
+        assert_ne!(draw(3), draw(4));
+    }
+
+    #[test]
+    fn test_freeze() {
+        // Frozen parameters keep their values and get no optimizer state.
+        let (xs, ys) = moons();
+        let mlp = model(0);
+        let before = mlp.state_dict();
+        let mut trainer = Trainer::new(Adam::default(), StepLr::new(0.05, 10, 0.5), 40, 8, 0)
+            .freeze(&["layers.0"]);
+        for _ in 0..10 {
+            trainer.step(&mlp, |b| hinge(&mlp, &xs, &ys, b));
+        }
+        for (name, p) in mlp.named_parameters() {
+            let unchanged = p.get_data().to_bits() == before.get(&name).unwrap().to_bits();
+            assert_eq!(unchanged, name.starts_with("layers.0."), "{}", name);
+        }
+        let state = trainer.optimizer().state_dict();
+        assert!(state.keys().all(|k| !k.contains(".layers.0.")));
+        assert!(state.get("m.layers.1.neurons.0.w0").is_some());
+    }
+
+    #[test]
+    fn test_resume() {
+        // 100 steps straight equal 47 steps, a checkpoint mid-epoch through a
+        // file into a fresh model and trainer, and 53 more, bit for bit.
+        let (xs, ys) = moons();
+        let adam = || Adam::new(0.9, 0.999, 1e-8);
+        let schedule = || StepLr::new(0.05, 30, 0.5);
+
+        let straight = model(0);
+        let mut trainer = Trainer::new(adam(), schedule(), 40, 8, 7);
+        for _ in 0..100 {
+            trainer.step(&straight, |b| hinge(&straight, &xs, &ys, b));
+        }
+
+        let first = model(0);
+        let mut first_trainer = Trainer::new(adam(), schedule(), 40, 8, 7);
+        for _ in 0..47 {
+            first_trainer.step(&first, |b| hinge(&first, &xs, &ys, b));
+        }
+        let path = std::env::temp_dir().join(format!("nn-resume-{}.nnck", std::process::id()));
+        first_trainer.checkpoint(&first).save(&path).unwrap();
+        drop((first, first_trainer));
+
+        // Different weights and seed, all overwritten by the checkpoint.
+        let resumed = model(1);
+        let mut resumed_trainer = Trainer::new(adam(), schedule(), 40, 8, 99);
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        resumed_trainer
+            .load_checkpoint(&resumed, &checkpoint)
+            .unwrap();
+        assert_eq!(resumed_trainer.steps(), 47);
+        assert_eq!(resumed_trainer.epoch(), 9);
+        assert_eq!(checkpoint.cursor, 16);
+        for _ in 0..53 {
+            resumed_trainer.step(&resumed, |b| hinge(&resumed, &xs, &ys, b));
+        }
+
+        assert_eq!(bits(&resumed), bits(&straight));
+        assert_eq!(
+            resumed_trainer.checkpoint(&resumed).to_bytes(),
+            trainer.checkpoint(&straight).to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_load_checkpoint_mismatch() {
+        let (xs, ys) = moons();
+        let mlp = model(0);
+        let mut trainer = Trainer::new(Adam::default(), StepLr::new(0.01, 10, 0.5), 40, 8, 0);
+        trainer.step(&mlp, |b| hinge(&mlp, &xs, &ys, b));
+        let checkpoint = trainer.checkpoint(&mlp);
+
+        // A different architecture is rejected before anything changes.
+        let other = MLP::new(2, &[4, 1]);
+        let before = bits(&other);
+        let mut fresh = Trainer::new(Adam::default(), StepLr::new(0.01, 10, 0.5), 40, 8, 0);
+        assert!(matches!(
+            fresh.load_checkpoint(&other, &checkpoint),
+            Err(NnError::StateDictMismatch { .. })
+        ));
+        assert_eq!(bits(&other), before);
+        assert_eq!(fresh.steps(), 0);
+        assert!(fresh
+            .optimizer()
+            .state_dict()
+            .get("m.layers.0.neurons.0.w0")
+            .is_none());
+
+        // A position past the end of a smaller dataset.
+        let mut small = Trainer::new(Adam::default(), StepLr::new(0.01, 10, 0.5), 4, 2, 0);
+        let mut far = checkpoint.clone();
+        far.cursor = 8;
+        assert!(matches!(
+            small.load_checkpoint(&other, &far),
+            Err(NnError::Format(_))
+        ));
+        assert_eq!(bits(&other), before);
+
+        // Optimizer state of the wrong kind too.
+        let mut sgd = Trainer::new(Sgd::new(), StepLr::new(0.01, 10, 0.5), 40, 8, 0);
+        assert!(sgd.load_checkpoint(&model(0), &checkpoint).is_err());
+    }
 }
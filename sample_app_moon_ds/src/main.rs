@@ -1,16 +1,13 @@
-use flame;
 use nn::mlp::mlp::MLP;
 use nn::mlp::module::Module;
 use nn::tensor::value::Value;
 use rand::seq::IteratorRandom;
 use sample_app_moon_ds::moon_data::{get_x, get_y};
 
-fn loss(X: &[[f64; 2]; 100], y: &[f64], model: &MLP, batch_size: usize) -> (Value, f64) {
-    let ri: Vec<usize> = (0..X.len())
-        .into_iter()
-        .choose_multiple(&mut rand::thread_rng(), batch_size);
+fn loss(x: &[[f64; 2]; 100], y: &[f64], model: &MLP, batch_size: usize) -> (Value, f64) {
+    let ri: Vec<usize> = (0..x.len()).choose_multiple(&mut rand::thread_rng(), batch_size);
 
-    let xb: Vec<[f64; 2]> = X
+    let xb: Vec<[f64; 2]> = x
         .iter()
         .enumerate()
         // .filter_map(|(i, x)| if ri.contains(&i) { Some(x) } else { None })
@@ -26,8 +23,13 @@ fn loss(X: &[[f64; 2]; 100], y: &[f64], model: &MLP, batch_size: usize) -> (Valu
     }
 
     // run model
-    let inputs = xb;
-    let scores: Vec<Value> = inputs.iter().map(|input| model.call(input)).collect();
+    let inputs: Vec<Vec<f64>> = xb.iter().map(|x| x.to_vec()).collect();
+    let scores: Vec<Value> = model
+        .forward_batch(&inputs)
+        .expect("moon inputs are 2-d")
+        .into_iter()
+        .map(|mut y| y.remove(0))
+        .collect();
 
     let losses: Value = yb
         .iter()
@@ -51,7 +53,7 @@ fn loss(X: &[[f64; 2]; 100], y: &[f64], model: &MLP, batch_size: usize) -> (Valu
         .iter()
         .zip(scores.iter())
         .map(|(&yi, scorei)| {
-            if ((*yi > 0.0) == (scorei.get_data() > 0.0)) {
+            if (*yi > 0.0) == (scorei.get_data() > 0.0) {
                 1.0
             } else {
                 0.0
@@ -68,10 +70,13 @@ fn main() {
     let batch_size = 32;
     // let model = MLP::new(2, &[16, 8, 8, 1]);
     let model = MLP::new(2, &[16, 16, 1]);
-    println!("number of parameters {}", model.parameters().len());
-    let X = get_x();
-    let Y = get_y();
-    let total_loss = loss(&X, &Y, &model, batch_size);
+    println!("number of parameters {}", model.num_parameters());
+    for (name, layer) in model.named_children() {
+        println!("  {}: {} parameters", name, layer.num_parameters());
+    }
+    let xs = get_x();
+    let ys = get_y();
+    let total_loss = loss(&xs, &ys, &model, batch_size);
     print!(
         "total_loss : {:?},{}",
         total_loss.0.get_data(),
@@ -82,7 +87,7 @@ fn main() {
     for i in 0..100 {
         // flame::start_guard("loss");
         // Step 1: Forward
-        let (total_loss, acc) = loss(&X, &Y, &model, batch_size);
+        let (total_loss, acc) = loss(&xs, &ys, &model, batch_size);
 
         // Step 2: backward
         model.zero_grad();
@@ -96,14 +101,27 @@ fn main() {
             let data = p.get_data() - (learning_rate * p.get_grad());
             p.set_data(data);
         });
-        if i % 1 == 0 {
-            println!(
-                "step: {} loss: {}, accuracy {}",
-                i,
-                total_loss.get_data(),
-                acc * 100.0
-            )
-        }
+        println!(
+            "step: {} loss: {}, accuracy {}",
+            i,
+            total_loss.get_data(),
+            acc * 100.0
+        );
     }
     // flame::end("my_program");
+
+    // Keep the trained weights: `sample_app_moon_ds model.nnsd` (or
+    // `model.json` for the JSON format).
+    if let Some(path) = std::env::args().nth(1) {
+        let state = model.state_dict();
+        let saved = if path.ends_with(".json") {
+            state.save_json(&path)
+        } else {
+            state.save(&path)
+        };
+        match saved {
+            Ok(()) => println!("saved {} parameters to {}", state.len(), path),
+            Err(e) => eprintln!("could not save to {}: {}", path, e),
+        }
+    }
 }